@@ -0,0 +1,290 @@
+#![deny(missing_docs)]
+//! Plays a game of Spider Solitaire from a script of moves, for automated testing.
+//!
+//! A script has one command per line, and `#` starts a comment:
+//!
+//! ```text
+//! variant spiderette
+//! seed 42
+//! difficulty medium
+//! 3:4->7
+//! deal
+//! undo
+//! ```
+//!
+//! The variant, seed and difficulty must come before the first move. Moves are written as
+//! `from:row->to`, counting columns and rows from one. In variants with a waste and foundation
+//! piles, single cards are played with `w->3`, `3->f` or `w->f`, and in FreeCell cards go in and
+//! out of the free cells with `3->c1` and `c1->5`.
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::str::FromStr;
+
+use spidertaire::game::{Action, Game};
+use spidertaire::variant::Variant;
+use spidertaire::Difficulty;
+
+/// The help text printed for `--help`.
+const USAGE: &str = "\
+Usage: spidertaire-cli [OPTIONS] [FILE]
+
+Reads a script of moves from FILE, or from stdin when FILE is missing or -,
+and prints the resulting position, score and status.
+
+Options:
+  --seed <SEED>              Deal the game with the given seed, overriding the script
+  --difficulty <DIFFICULTY>  Play at the given difficulty, overriding the script
+  --variant <VARIANT>        Play the given variant, overriding the script
+  -h, --help                 Print this help";
+
+/// A line of a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Sets the variant played.
+    Variant(Variant),
+    /// Sets the seed the game is dealt with.
+    Seed(u64),
+    /// Sets the difficulty the game is dealt at.
+    Difficulty(Difficulty),
+    /// Moves, deals or undoes.
+    Action(Action),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(char::is_whitespace) {
+            Some(("seed", seed)) => seed
+                .trim()
+                .parse()
+                .map(Command::Seed)
+                .map_err(|e| format!("invalid seed {:?}: {}", seed.trim(), e)),
+            Some(("difficulty", difficulty)) => difficulty.trim().parse().map(Command::Difficulty),
+            Some(("variant", variant)) => variant.trim().parse().map(Command::Variant),
+            _ => s.parse().map(Command::Action),
+        }
+    }
+}
+
+/// The options read from the command line.
+#[derive(Debug, Default)]
+struct Args {
+    seed: Option<u64>,
+    difficulty: Option<Difficulty>,
+    variant: Option<Variant>,
+    path: Option<String>,
+}
+
+impl Args {
+    /// Fills in any options not given on the command line from the script.
+    fn or(&self, script: &Args) -> Args {
+        Args {
+            seed: self.seed.or(script.seed),
+            difficulty: self.difficulty.or(script.difficulty),
+            variant: self.variant.or(script.variant),
+            path: None,
+        }
+    }
+
+    /// Deals the game the script is played on.
+    fn new_game(&self) -> Result<Game, String> {
+        self.seed
+            .map(|seed| {
+                Game::with_variant(
+                    self.variant.unwrap_or_default(),
+                    self.difficulty.unwrap_or_default(),
+                    seed,
+                )
+            })
+            .ok_or_else(|| String::from("no seed was given"))
+    }
+}
+
+/// Parses the command-line arguments, excluding the program name.
+/// Returns `Ok(None)` when help was asked for.
+fn parse_args<I>(args: I) -> Result<Option<Args>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--seed" => {
+                let value = value()?;
+                parsed.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|e| format!("invalid value {:?} for --seed: {}", value, e))?,
+                );
+            }
+            "--difficulty" => parsed.difficulty = Some(value()?.parse()?),
+            "--variant" => parsed.variant = Some(value()?.parse()?),
+            "-h" | "--help" => return Ok(None),
+            _ if parsed.path.is_none() && (arg == "-" || !arg.starts_with('-')) => {
+                parsed.path = Some(arg)
+            }
+            _ => return Err(format!("unexpected argument {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+/// Plays the script, returning the game after its last command. The variant, seed and difficulty
+/// given on the command line take precedence over those in the script.
+/// Fails with the line number on the first command that cannot be parsed or is not legal.
+fn play(script: &str, args: &Args) -> Result<Game, String> {
+    let mut script_args = Args::default();
+    let mut game = None;
+    for (index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |e: String| format!("line {}: {}", index + 1, e);
+        let command = line.parse::<Command>().map_err(error)?;
+        let result = match (command, &mut game) {
+            (Command::Variant(_) | Command::Seed(_) | Command::Difficulty(_), Some(_)) => Err(
+                String::from("the variant, seed and difficulty must come before the first move"),
+            ),
+            (Command::Variant(variant), None) => {
+                script_args.variant = Some(variant);
+                Ok(())
+            }
+            (Command::Seed(seed), None) => {
+                script_args.seed = Some(seed);
+                Ok(())
+            }
+            (Command::Difficulty(difficulty), None) => {
+                script_args.difficulty = Some(difficulty);
+                Ok(())
+            }
+            (Command::Action(action), game) => {
+                let game = match game {
+                    Some(game) => game,
+                    None => game.insert(args.or(&script_args).new_game().map_err(error)?),
+                };
+                game.perform(action).map_err(|e| match action {
+                    Action::Move(_) => format!("illegal move {}: {}", action, e),
+                    Action::Play(_) => format!("illegal play {}: {}", action, e),
+                    Action::Deal | Action::Undo => format!("unable to {}: {}", action, e),
+                })
+            }
+        };
+        result.map_err(error)?;
+    }
+    match game {
+        Some(game) => Ok(game),
+        None => args.or(&script_args).new_game(),
+    }
+}
+
+/// Reads the script from a file, or from stdin when there is no file or it is `-`.
+fn read_script(path: Option<&str>) -> Result<String, String> {
+    match path {
+        Some(path) if path != "-" => {
+            fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path, e))
+        }
+        _ => {
+            let mut script = String::new();
+            io::stdin()
+                .read_to_string(&mut script)
+                .map_err(|e| format!("unable to read stdin: {}", e))?;
+            Ok(script)
+        }
+    }
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    let game = read_script(args.path.as_deref()).and_then(|script| play(&script, &args));
+    match game {
+        Ok(game) => {
+            println!(
+                "{} ({}), seed {}",
+                game.variant().as_str(),
+                game.difficulty(),
+                game.seed()
+            );
+            println!("{}", game);
+            println!(
+                "Score {}, moves {}, status {}",
+                game.score(),
+                game.moves(),
+                game.status().as_str()
+            );
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_the_script_through_the_engine() {
+        let game = play(
+            "# a comment\nseed 42\n\ndeal\nundo  # back again\n",
+            &Args::default(),
+        )
+        .unwrap();
+        assert_eq!(game.seed(), 42);
+        assert_eq!(game.difficulty(), Difficulty::Easy);
+        assert_eq!(game.stock().len(), 5);
+        assert_eq!(game.moves(), 2);
+    }
+
+    #[test]
+    fn command_line_overrides_the_script() {
+        let args = Args {
+            seed: Some(2),
+            ..Args::default()
+        };
+        let game = play("seed 1\ndifficulty hard\nvariant spiderette\n", &args).unwrap();
+        assert_eq!(game.seed(), 2);
+        assert_eq!(game.difficulty(), Difficulty::Hard);
+        assert_eq!(game.variant(), Variant::Spiderette);
+        assert_eq!(game.columns().len(), 7);
+    }
+
+    #[test]
+    fn fails_on_the_first_bad_line() {
+        assert_eq!(
+            play("seed 42\n1:1->2\n", &Args::default()).unwrap_err(),
+            "line 2: illegal move 1:1->2: \
+             the cards from row 1 of column 1 are not a face up run in one suit"
+        );
+        assert!(play("deal\n", &Args::default())
+            .unwrap_err()
+            .starts_with("line 1:"));
+        assert!(play("seed 42\ndeal\nseed 7\n", &Args::default())
+            .unwrap_err()
+            .starts_with("line 3:"));
+        assert!(play("seed 42\nshuffle\n", &Args::default())
+            .unwrap_err()
+            .starts_with("line 2:"));
+        assert_eq!(
+            play("seed 42\nw->1\n", &Args::default()).unwrap_err(),
+            "line 2: illegal play w->1: the waste is empty"
+        );
+    }
+}
@@ -0,0 +1,663 @@
+#![deny(missing_docs)]
+//! Spider Solitaire in the terminal, for playing over SSH and trying out the rules without a window.
+
+use std::env;
+use std::io::{self, Write};
+use std::process;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+
+use spidertaire::game::{Game, Move, Pile, Play, Status};
+use spidertaire::variant::Variant;
+use spidertaire::{Card, CardColor, Difficulty};
+
+/// The help text printed for `--help`.
+const USAGE: &str = "\
+Usage: spidertaire-tui [OPTIONS]
+
+Options:
+  --seed <SEED>              Deal the first game with the given seed, which is the deal
+                             number in FreeCell
+  --difficulty <DIFFICULTY>  Play at the given difficulty: easy, medium, hard, or custom
+                             suits like custom-hcs or custom-hcs-by-colour
+  --variant <VARIANT>        Play the given variant: spider, spiderette, scorpion,
+                             will-o-the-wisp, spiderwort, klondike,
+                             klondike-draw-three or freecell
+  -h, --help                 Print this help
+
+In the game, the digits 1-9 and 0 pick the first ten columns, and alt with a digit picks
+columns 11-20, as in Spiderwort.";
+
+/// The keys shown along the bottom of the screen.
+const KEYS: &str = "\u{2190}\u{2192}/1-0 column  \u{2191}\u{2193} run length  enter pick up/drop  esc cancel  d deal  u undo  ? hint  n new game  q quit";
+/// The keys shown below [`KEYS`] in variants with more than ten columns.
+const WIDE_KEYS: &str = "alt+1-0 columns 11-20";
+/// The keys shown below [`KEYS`] in variants with a waste and foundation piles.
+const PILE_KEYS: &str = "w pick up waste  f play to foundation";
+/// The keys shown below [`KEYS`] in variants with free cells.
+const CELL_KEYS: &str = "c put in free cell  F1-F4 pick up free cell  f play to foundation";
+/// The width of a card, not including the space between columns.
+const CARD_WIDTH: usize = 5;
+/// The row of the screen the first card of each column is drawn on.
+const TABLEAU_ROW: u16 = 3;
+
+/// What the player asks for with a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Leaves the game.
+    Quit,
+    /// Moves the cursor one column to the left, wrapping around.
+    Left,
+    /// Moves the cursor one column to the right, wrapping around.
+    Right,
+    /// Moves the cursor to the column at the given index.
+    Column(usize),
+    /// Takes one more card with the held run.
+    LongerRun,
+    /// Takes one fewer card with the held run.
+    ShorterRun,
+    /// Picks up the run under the cursor, or drops the held cards there.
+    PickUpOrDrop,
+    /// Puts the held cards back.
+    Cancel,
+    /// Deals from the stock.
+    Deal,
+    /// Undoes the last move.
+    Undo,
+    /// Picks up the top card of the waste.
+    PickUpWaste,
+    /// Plays the held card, or the bottom card under the cursor, to the foundation.
+    PlayToFoundation,
+    /// Puts the bottom card of the held run's column, or the column under the cursor, in a free cell.
+    PlayToFreeCell,
+    /// Picks up the card in the free cell at the given index.
+    PickUpCell(usize),
+    /// Points out a move.
+    Hint,
+    /// Deals a new game of the same variant and difficulty.
+    NewGame,
+}
+
+/// Gets the command a key press asks for. The digits 1 to 9 pick the first nine columns and 0 the
+/// tenth, and holding alt picks the next ten, for variants with more than ten columns.
+fn command(key: KeyEvent) -> Option<Command> {
+    let command = match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Command::Quit,
+        KeyCode::Char('q') => Command::Quit,
+        KeyCode::Left => Command::Left,
+        KeyCode::Right => Command::Right,
+        KeyCode::Char(c @ '0'..='9') => {
+            let digit = c.to_digit(10).unwrap() as usize;
+            let column = (digit + 9) % 10;
+            if key.modifiers.contains(KeyModifiers::ALT) {
+                Command::Column(column + 10)
+            } else {
+                Command::Column(column)
+            }
+        }
+        KeyCode::Up => Command::LongerRun,
+        KeyCode::Down => Command::ShorterRun,
+        KeyCode::Enter | KeyCode::Char(' ') => Command::PickUpOrDrop,
+        KeyCode::Esc => Command::Cancel,
+        KeyCode::Char('d') => Command::Deal,
+        KeyCode::Char('u') => Command::Undo,
+        KeyCode::Char('w') => Command::PickUpWaste,
+        KeyCode::Char('f') => Command::PlayToFoundation,
+        KeyCode::Char('c') => Command::PlayToFreeCell,
+        KeyCode::F(n @ 1..=4) => Command::PickUpCell(n as usize - 1),
+        KeyCode::Char('?') => Command::Hint,
+        KeyCode::Char('n') => Command::NewGame,
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Cards the player has picked up to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Held {
+    /// The run starting at the given column and row.
+    Run(usize, usize),
+    /// The top card of the waste.
+    Waste,
+    /// The card in the free cell at the given index.
+    Cell(usize),
+}
+
+/// The player's progress through choosing a move, along with the game being played.
+struct Tui {
+    game: Game,
+    /// The column the player is pointing at.
+    cursor: usize,
+    /// The cards picked up, if any.
+    held: Option<Held>,
+    /// The move pointed out by the last hint, until the game changes.
+    hint: Option<Move>,
+    /// A message about the last action, such as why a move was not allowed.
+    message: String,
+}
+
+impl Tui {
+    fn new(game: Game) -> Self {
+        let message = format!("Dealt {} game with seed {}", game.difficulty(), game.seed());
+        Tui {
+            game,
+            cursor: 0,
+            held: None,
+            hint: None,
+            message,
+        }
+    }
+
+    /// Shows the outcome of an action, clearing any held cards and hint once the game changes.
+    fn report(&mut self, result: Result<(), String>, done: &str) {
+        match result {
+            Ok(()) => {
+                self.held = None;
+                self.hint = None;
+                self.message = match self.game.status() {
+                    Status::Playing => String::from(done),
+                    Status::Won => String::from("You won! Press n for a new game"),
+                    Status::Lost => {
+                        String::from("No moves remain. Press u to undo or n for a new game")
+                    }
+                };
+            }
+            Err(e) => self.message = e,
+        }
+    }
+
+    /// Picks up the longest run at the bottom of the column under the cursor, or drops the held run there.
+    fn pick_up_or_drop(&mut self) {
+        match self.held {
+            None => {
+                let column = &self.game.columns()[self.cursor];
+                if column.is_empty() {
+                    self.message = String::from("There are no cards to pick up");
+                } else {
+                    let row = column.len() - self.game.movable_run(self.cursor);
+                    self.held = Some(Held::Run(self.cursor, row));
+                    self.message.clear();
+                }
+            }
+            Some(Held::Waste) => {
+                let result = self.game.play(Play {
+                    from: Pile::Waste,
+                    to: Pile::Column(self.cursor),
+                });
+                self.report(result, "");
+            }
+            Some(Held::Cell(cell)) => {
+                let result = self.game.play(Play {
+                    from: Pile::Cell(cell),
+                    to: Pile::Column(self.cursor),
+                });
+                self.report(result, "");
+            }
+            Some(Held::Run(from, _)) if from == self.cursor => {
+                self.held = None;
+                self.message.clear();
+            }
+            Some(Held::Run(from, row)) => {
+                // when the held run does not fit, drop the part of it that does
+                let length = self.game.columns()[from].len();
+                let legal_move = (row..length)
+                    .map(|row| Move {
+                        from,
+                        row,
+                        to: self.cursor,
+                    })
+                    .find(|legal_move| self.game.check_move(*legal_move).is_ok())
+                    .unwrap_or(Move {
+                        from,
+                        row,
+                        to: self.cursor,
+                    });
+                let result = self.game.apply(legal_move);
+                self.report(result, "");
+            }
+        }
+    }
+
+    /// Takes one more or one fewer card with the held run, within the run that can be moved.
+    fn change_held_length(&mut self, more: bool) {
+        if let Some(Held::Run(from, row)) = self.held {
+            let length = self.game.columns()[from].len();
+            let top = length - self.game.movable_run(from);
+            let row = if more {
+                row.saturating_sub(1).max(top)
+            } else {
+                (row + 1).min(length - 1)
+            };
+            self.held = Some(Held::Run(from, row));
+        }
+    }
+
+    /// Plays the held card, or the bottom card of the column under the cursor, to the foundation.
+    fn play_to_foundation(&mut self) {
+        let from = match self.held {
+            Some(Held::Waste) => Pile::Waste,
+            Some(Held::Cell(cell)) => Pile::Cell(cell),
+            Some(Held::Run(from, _)) => Pile::Column(from),
+            None => Pile::Column(self.cursor),
+        };
+        let result = self.game.play(Play {
+            from,
+            to: Pile::Foundation,
+        });
+        self.report(result, "Played a card to the foundation");
+    }
+
+    /// Puts the bottom card of the held run's column, or of the column under the cursor, into the
+    /// first empty free cell.
+    fn play_to_free_cell(&mut self) {
+        let from = match self.held {
+            Some(Held::Run(from, _)) => from,
+            _ => self.cursor,
+        };
+        let result = match self.game.free_cells().iter().position(Option::is_none) {
+            Some(cell) => self.game.play(Play {
+                from: Pile::Column(from),
+                to: Pile::Cell(cell),
+            }),
+            None => Err(String::from("the free cells are full")),
+        };
+        self.report(result, "Put a card in a free cell");
+    }
+
+    /// Picks up the card in the given free cell.
+    fn pick_up_cell(&mut self, cell: usize) {
+        match self.game.free_cells().get(cell) {
+            Some(Some(_)) => {
+                self.held = Some(Held::Cell(cell));
+                self.message.clear();
+            }
+            Some(None) => self.message = format!("Free cell {} is empty", cell + 1),
+            None => {}
+        }
+    }
+
+    /// Points out a play to the foundation, or failing that a move, or failing that a play from the waste.
+    fn show_hint(&mut self) {
+        let play = self.game.hint_play();
+        let foundation_play = play.filter(|play| play.to == Pile::Foundation);
+        self.hint = match foundation_play {
+            Some(_) => None,
+            None => self.game.hint(),
+        };
+        let can_deal = !self.game.stock().is_empty() || !self.game.waste().is_empty();
+        self.message = match (foundation_play, self.hint, play) {
+            (Some(play), _, _) | (None, None, Some(play)) => format!(
+                "Try playing {} to {}",
+                pile_name(play.from),
+                pile_name(play.to)
+            ),
+            (None, Some(hint), _) => format!(
+                "Try moving column {} row {} to column {}",
+                hint.from + 1,
+                hint.row + 1,
+                hint.to + 1
+            ),
+            (None, None, None) if can_deal => String::from("Try dealing"),
+            (None, None, None) => String::from("There are no useful moves"),
+        };
+    }
+
+    /// Handles a key press, returning false when the player quits.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        command(key).is_none_or(|command| self.apply(command))
+    }
+
+    /// Carries out a command, returning false when the player quits. Commands for piles the
+    /// variant does not have are ignored.
+    fn apply(&mut self, command: Command) -> bool {
+        let columns = self.game.columns().len();
+        match command {
+            Command::Quit => return false,
+            Command::Left => self.cursor = (self.cursor + columns - 1) % columns,
+            Command::Right => self.cursor = (self.cursor + 1) % columns,
+            Command::Column(column) => {
+                if column < columns {
+                    self.cursor = column;
+                }
+            }
+            Command::LongerRun => self.change_held_length(true),
+            Command::ShorterRun => self.change_held_length(false),
+            Command::PickUpOrDrop => self.pick_up_or_drop(),
+            Command::Cancel => {
+                self.held = None;
+                self.message.clear();
+            }
+            Command::Deal => {
+                let result = self.game.deal();
+                self.report(result, "Dealt a set from the stock");
+            }
+            Command::Undo => {
+                let result = self.game.undo();
+                self.report(result, "Undid the last move");
+            }
+            Command::PickUpWaste if self.game.rules().has_waste() => {
+                if self.game.waste().is_empty() {
+                    self.message = String::from("The waste is empty");
+                } else {
+                    self.held = Some(Held::Waste);
+                    self.message.clear();
+                }
+            }
+            Command::PlayToFoundation if self.game.rules().builds_foundation() => {
+                self.play_to_foundation()
+            }
+            Command::PlayToFreeCell if !self.game.free_cells().is_empty() => {
+                self.play_to_free_cell()
+            }
+            Command::PickUpCell(cell) => self.pick_up_cell(cell),
+            Command::Hint => self.show_hint(),
+            Command::NewGame => {
+                *self = Tui::new(Game::with_variant(
+                    self.game.variant(),
+                    self.game.difficulty(),
+                    self.game.rules().random_seed(),
+                ))
+            }
+            Command::PickUpWaste | Command::PlayToFoundation | Command::PlayToFreeCell => {}
+        }
+        true
+    }
+
+    /// Draws the whole screen.
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(
+            out,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(format!(
+                "Spidertaire \u{2014} {} ({}), seed {}",
+                self.game.variant().as_str(),
+                self.game.difficulty(),
+                self.game.seed()
+            )),
+            cursor::MoveTo(0, 1),
+            Print(format!(
+                "Score {}  Moves {}  Stock {}  Runs {}/{}  {}",
+                self.game.score(),
+                self.game.moves(),
+                self.game.stock().len(),
+                self.game.foundation().len(),
+                self.game.runs_to_win(),
+                self.game.status().as_str()
+            )),
+        )?;
+        if self.game.rules().has_waste() {
+            queue!(out, Print("  Waste "))?;
+            if self.held == Some(Held::Waste) {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            match self.game.waste().last() {
+                Some(card) => queue_card(out, *card)?,
+                None => queue!(out, Print(" --  "))?,
+            }
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        if !self.game.free_cells().is_empty() {
+            queue!(out, Print("  Cells"))?;
+            for (index, cell) in self.game.free_cells().iter().enumerate() {
+                if self.held == Some(Held::Cell(index)) {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                match cell {
+                    Some(card) => queue_card(out, *card)?,
+                    None => queue!(out, Print(" --  "))?,
+                }
+                queue!(out, SetAttribute(Attribute::Reset))?;
+            }
+        }
+        if self.game.rules().builds_foundation() {
+            queue!(out, Print(" Foundation"))?;
+            for pile in self.game.foundation_piles() {
+                match pile.last() {
+                    Some(card) => queue_card(out, *card)?,
+                    None => queue!(out, Print(" --  "))?,
+                }
+            }
+        }
+        for x in 0..self.game.columns().len() {
+            let header = format!("{:^width$}", (x + 1) % 10, width = CARD_WIDTH);
+            queue!(out, cursor::MoveTo(column_left(x), TABLEAU_ROW - 1))?;
+            if x == self.cursor {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(header),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(header))?;
+            }
+        }
+        let mut bottom = TABLEAU_ROW;
+        for (x, column) in self.game.columns().iter().enumerate() {
+            for (y, tableau_card) in column.iter().enumerate() {
+                let held = matches!(self.held, Some(Held::Run(from, row)) if from == x && y >= row);
+                let hinted = self.hint.is_some_and(|hint| {
+                    (hint.from == x && y >= hint.row) || (hint.to == x && y + 1 == column.len())
+                });
+                let row = TABLEAU_ROW + y as u16;
+                bottom = bottom.max(row + 1);
+                queue!(out, cursor::MoveTo(column_left(x), row))?;
+                if held {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                } else if hinted {
+                    queue!(out, SetAttribute(Attribute::Underlined))?;
+                }
+                if tableau_card.face_up {
+                    queue_card(out, tableau_card.card)?;
+                } else {
+                    queue!(out, Print("[###]"))?;
+                }
+                queue!(out, SetAttribute(Attribute::Reset))?;
+            }
+            if column.is_empty() && self.hint.is_some_and(|hint| hint.to == x) {
+                queue!(
+                    out,
+                    cursor::MoveTo(column_left(x), TABLEAU_ROW),
+                    SetAttribute(Attribute::Underlined),
+                    Print("     "),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            }
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, bottom + 1),
+            Print(&self.message),
+            cursor::MoveTo(0, bottom + 2),
+            Print(KEYS),
+        )?;
+        if self.game.rules().has_waste() {
+            queue!(out, cursor::MoveTo(0, bottom + 3), Print(PILE_KEYS))?;
+        } else if !self.game.free_cells().is_empty() {
+            queue!(out, cursor::MoveTo(0, bottom + 3), Print(CELL_KEYS))?;
+        } else if self.game.columns().len() > 10 {
+            queue!(out, cursor::MoveTo(0, bottom + 3), Print(WIDE_KEYS))?;
+        }
+        out.flush()
+    }
+}
+
+/// Draws a face up card, with red suits in red.
+fn queue_card(out: &mut impl Write, card: Card) -> io::Result<()> {
+    let color = match card.color() {
+        CardColor::Red => Color::Red,
+        CardColor::Black => Color::Reset,
+    };
+    let name = format!("{}{}", card.value.as_str(), card.suit.as_str());
+    queue!(
+        out,
+        SetForegroundColor(color),
+        Print(format!(" {:<3} ", name)),
+        ResetColor
+    )
+}
+
+/// Names a pile in a hint.
+fn pile_name(pile: Pile) -> String {
+    match pile {
+        Pile::Column(column) => format!("column {}", column + 1),
+        Pile::Waste => String::from("the waste"),
+        Pile::Cell(cell) => format!("free cell {}", cell + 1),
+        Pile::Foundation => String::from("the foundation"),
+    }
+}
+
+/// Gets the screen column the cards of a tableau column are drawn from.
+fn column_left(x: usize) -> u16 {
+    (x * (CARD_WIDTH + 1)) as u16
+}
+
+/// Parses the command-line arguments into the variant, difficulty and seed of the first game.
+/// Returns `Ok(None)` when help was asked for.
+fn parse_args<I>(args: I) -> Result<Option<(Variant, Difficulty, u64)>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut variant = Variant::default();
+    let mut difficulty = Difficulty::default();
+    let mut seed = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--seed" => {
+                let value = value()?;
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|e| format!("invalid value {:?} for --seed: {}", value, e))?,
+                );
+            }
+            "--difficulty" => difficulty = value()?.parse()?,
+            "--variant" => variant = value()?.parse()?,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unexpected argument {:?}\n\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Some((
+        variant,
+        difficulty,
+        seed.unwrap_or_else(|| variant.rules().random_seed()),
+    )))
+}
+
+/// Plays until the player quits, redrawing after every key press.
+fn run(tui: &mut Tui, out: &mut impl Write) -> io::Result<()> {
+    loop {
+        tui.draw(out)?;
+        match event::read()? {
+            // only presses are handled, as some terminals also report releases
+            Event::Key(key) if key.kind == KeyEventKind::Press && !tui.handle_key(key) => {
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    let (variant, difficulty, seed) = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    let mut tui = Tui::new(Game::with_variant(variant, difficulty, seed));
+    let mut out = io::stdout();
+    let result = terminal::enable_raw_mode()
+        .and_then(|_| execute!(out, EnterAlternateScreen, cursor::Hide))
+        .and_then(|_| run(&mut tui, &mut out));
+    // the terminal is restored even when drawing failed, so the error can be read
+    let restored = execute!(out, cursor::Show, LeaveAlternateScreen)
+        .and_then(|_| terminal::disable_raw_mode());
+    if let Err(e) = result.and(restored) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    println!(
+        "Finished {} game with seed {}: score {}",
+        tui.game.difficulty(),
+        tui.game.seed(),
+        tui.game.score()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn digits_pick_columns_and_alt_picks_columns_past_ten() {
+        assert_eq!(command(key(KeyCode::Char('1'))), Some(Command::Column(0)));
+        assert_eq!(command(key(KeyCode::Char('0'))), Some(Command::Column(9)));
+        let alt_three = KeyEvent::new(KeyCode::Char('3'), KeyModifiers::ALT);
+        assert_eq!(command(alt_three), Some(Command::Column(12)));
+
+        let mut tui = Tui::new(Game::with_variant(Variant::Spiderwort, Difficulty::Easy, 1));
+        assert!(tui.handle_key(alt_three));
+        assert_eq!(tui.cursor, 12);
+        assert!(tui.handle_key(key(KeyCode::Right)));
+        assert_eq!(tui.cursor, 0);
+
+        let mut tui = Tui::new(Game::new(Difficulty::Easy, 1));
+        tui.handle_key(key(KeyCode::Char('4')));
+        tui.handle_key(alt_three);
+        assert_eq!(tui.cursor, 3);
+    }
+
+    #[test]
+    fn picks_up_and_drops_runs() {
+        let mut tui = Tui::new(Game::new(Difficulty::Easy, 1));
+        let hint = tui.game.hint().unwrap();
+        let dealt = tui.game.columns()[hint.to].len();
+        tui.apply(Command::Column(hint.from));
+        tui.apply(Command::PickUpOrDrop);
+        assert!(matches!(tui.held, Some(Held::Run(from, _)) if from == hint.from));
+        tui.apply(Command::Column(hint.to));
+        tui.apply(Command::PickUpOrDrop);
+        assert_eq!(tui.held, None);
+        assert_eq!(tui.game.moves(), 1);
+        tui.apply(Command::Undo);
+        assert_eq!(tui.game.moves(), 2);
+        assert_eq!(tui.game.columns()[hint.to].len(), dealt);
+    }
+
+    #[test]
+    fn ignores_piles_the_variant_does_not_have() {
+        let mut tui = Tui::new(Game::new(Difficulty::Easy, 1));
+        for code in [KeyCode::Char('w'), KeyCode::Char('f'), KeyCode::Char('c')] {
+            assert!(tui.handle_key(key(code)));
+        }
+        assert_eq!(tui.held, None);
+        assert_eq!(tui.game.moves(), 0);
+
+        let mut tui = Tui::new(Game::with_variant(Variant::Klondike, Difficulty::Easy, 1));
+        tui.apply(Command::PickUpWaste);
+        assert_eq!(tui.message, "The waste is empty");
+        tui.apply(Command::Deal);
+        tui.apply(Command::PickUpWaste);
+        assert_eq!(tui.held, Some(Held::Waste));
+
+        assert!(!tui.handle_key(key(KeyCode::Char('q'))));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(!tui.handle_key(ctrl_c));
+    }
+}
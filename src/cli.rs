@@ -0,0 +1,139 @@
+//! Command-line arguments, for jumping straight into a particular deal.
+
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use spidertaire::variant::Variant;
+use spidertaire::Difficulty;
+
+/// The help text printed for `--help`.
+pub const USAGE: &str = "\
+Usage: spidertaire [OPTIONS]
+
+Options:
+  --seed <SEED>              Deal the game with the given seed, which is the deal number
+                             in FreeCell
+  --difficulty <DIFFICULTY>  Play at the given difficulty: easy, medium, hard, or custom
+                             suits like custom-hcs or custom-hcs-by-colour
+  --variant <VARIANT>        Play the given variant: spider, spiderette, scorpion,
+                             will-o-the-wisp, spiderwort, klondike,
+                             klondike-draw-three or freecell
+  --load <FILE>              Read the seed, difficulty and variant from a TOML file, with
+                             values written as on the command line
+  --width <WIDTH>            Open the window with the given width
+  --height <HEIGHT>          Open the window with the given height
+  --replay <FILE>            Watch a replay instead of playing
+  --headless                 Print the deal instead of opening a window
+  -h, --help                 Print this help";
+
+/// The options read from the command line, and from any file given with `--load`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Args {
+    /// The seed the first game is dealt with.
+    pub seed: Option<u64>,
+    /// The difficulty of the first game.
+    pub difficulty: Option<Difficulty>,
+    /// The variant to play, read from a file by the same names as `--variant`.
+    #[serde(deserialize_with = "deserialize_variant")]
+    pub variant: Option<Variant>,
+    /// The width of the window, in logical pixels.
+    #[serde(skip)]
+    pub width: Option<f32>,
+    /// The height of the window, in logical pixels.
+    #[serde(skip)]
+    pub height: Option<f32>,
+    /// A replay to watch instead of playing.
+    #[serde(skip)]
+    pub replay: Option<PathBuf>,
+    /// Whether the deal is printed instead of opening a window.
+    #[serde(skip)]
+    pub headless: bool,
+}
+
+impl Args {
+    /// Determines whether a particular deal was asked for, so the menu should be skipped.
+    pub fn skips_menu(&self) -> bool {
+        self.seed.is_some() || self.difficulty.is_some() || self.variant.is_some()
+    }
+
+    /// Fills in any options not given on the command line from a loaded file.
+    fn or(self, loaded: Args) -> Args {
+        Args {
+            seed: self.seed.or(loaded.seed),
+            difficulty: self.difficulty.or(loaded.difficulty),
+            variant: self.variant.or(loaded.variant),
+            ..self
+        }
+    }
+}
+
+/// Reads a variant from a loaded file the way `--variant` does, by its id or display name.
+fn deserialize_variant<'de, D>(deserializer: D) -> Result<Option<Variant>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|variant| variant.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Parses the value following an option.
+fn parse_value<T: FromStr>(option: &str, value: Option<String>) -> Result<T, String>
+where
+    T::Err: ToString,
+{
+    let value = value.ok_or(format!("{} needs a value", option))?;
+    value.parse().map_err(|e: T::Err| {
+        format!(
+            "invalid value {:?} for {}: {}",
+            value,
+            option,
+            e.to_string()
+        )
+    })
+}
+
+/// Parses the command-line arguments, excluding the program name.
+/// Returns `Ok(None)` when help was asked for.
+pub fn parse<I>(args: I) -> Result<Option<Args>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut load = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // accept both `--option value` and `--option=value`
+        let (option, mut value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => {
+                (option.to_owned(), Some(value.to_owned()))
+            }
+            _ => (arg, None),
+        };
+        let mut next_value = || value.take().or_else(|| args.next());
+        match option.as_str() {
+            "--seed" => parsed.seed = Some(parse_value(&option, next_value())?),
+            "--difficulty" => parsed.difficulty = Some(parse_value(&option, next_value())?),
+            "--variant" => parsed.variant = Some(parse_value(&option, next_value())?),
+            "--load" => load = Some(parse_value::<String>(&option, next_value())?),
+            "--width" => parsed.width = Some(parse_value(&option, next_value())?),
+            "--height" => parsed.height = Some(parse_value(&option, next_value())?),
+            "--replay" => parsed.replay = Some(parse_value(&option, next_value())?),
+            "--headless" => parsed.headless = true,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unexpected argument {:?}\n\n{}", option, USAGE)),
+        }
+    }
+    if let Some(path) = load {
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+        let loaded = toml::from_str::<Args>(&contents)
+            .map_err(|e| format!("unable to parse {}: {}", path, e))?;
+        parsed = parsed.or(loaded);
+    }
+    Ok(Some(parsed))
+}
@@ -0,0 +1,1387 @@
+//! The rules of Spider and its variants, independent of how the game is drawn.
+//!
+//! A [`Game`] holds the cards in the tableau, the stock, the waste, the free cells and the
+//! foundation, and only changes through [`Game::apply`], [`Game::play`], [`Game::deal`] and
+//! [`Game::undo`], each of which checks the move is legal under the [`Rules`] of its [`Variant`].
+
+use std::fmt;
+use std::iter;
+use std::mem;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::variant::{Rules, Variant};
+use crate::{Card, CardSuit, CardValue, Difficulty};
+
+/// The score at the start of a game.
+const STARTING_SCORE: i32 = 500;
+/// The points gained for each completed run with a single suit in play.
+const RUN_SCORE: i32 = 100;
+/// The extra points gained for each completed run for every suit in play beyond the first.
+/// Half as many are gained when runs are grouped by colour.
+const EXTRA_SUIT_SCORE: i32 = 50;
+
+/// A card in the tableau, along with whether it is face up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableauCard {
+    /// The card.
+    pub card: Card,
+    /// Whether the card is face up.
+    pub face_up: bool,
+}
+
+/// A move of a run of cards from one column to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    /// The column the run is moved from, counting from zero.
+    pub from: usize,
+    /// The row of the first card of the run, counting from zero at the top of the column.
+    pub row: usize,
+    /// The column the run is moved to, counting from zero.
+    pub to: usize,
+}
+
+/// Writes the move as `from:row->to`, counting columns and rows from one.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}->{}", self.from + 1, self.row + 1, self.to + 1)
+    }
+}
+
+/// Parses a move written as `from:row->to`, counting columns and rows from one.
+///
+/// ```
+/// use spidertaire::game::Move;
+///
+/// let parsed = "3:4->7".parse::<Move>();
+/// assert_eq!(parsed, Ok(Move { from: 2, row: 3, to: 6 }));
+/// assert_eq!(parsed.unwrap().to_string(), "3:4->7");
+/// assert!("3->7".parse::<Move>().is_err());
+/// assert!("0:1->2".parse::<Move>().is_err());
+/// ```
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a move like 3:4->7, found {:?}", s);
+        let (source, to) = s.split_once("->").ok_or_else(invalid)?;
+        let (from, row) = source.split_once(':').ok_or_else(invalid)?;
+        let parse = |number: &str| match number.trim().parse::<usize>() {
+            Ok(number) if number > 0 => Ok(number - 1),
+            _ => Err(invalid()),
+        };
+        Ok(Move {
+            from: parse(from)?,
+            row: parse(row)?,
+            to: parse(to)?,
+        })
+    }
+}
+
+/// A pile a single card is played from or to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pile {
+    /// A column of the tableau, counting from zero.
+    Column(usize),
+    /// The waste, where cards dealt from the stock are turned face up.
+    Waste,
+    /// A free cell, counting from zero, which holds a single card.
+    Cell(usize),
+    /// The foundation, where cards are built up by suit.
+    Foundation,
+}
+
+/// Writes the pile as its column counting from one, `w` for the waste, `c` and the free cell
+/// counting from one, or `f` for the foundation.
+impl fmt::Display for Pile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pile::Column(column) => write!(f, "{}", column + 1),
+            Pile::Waste => write!(f, "w"),
+            Pile::Cell(cell) => write!(f, "c{}", cell + 1),
+            Pile::Foundation => write!(f, "f"),
+        }
+    }
+}
+
+/// Parses a pile written as its column counting from one, `w` for the waste, `c` and the free cell
+/// counting from one, or `f` for the foundation.
+///
+/// ```
+/// use spidertaire::game::Pile;
+///
+/// assert_eq!("3".parse(), Ok(Pile::Column(2)));
+/// assert_eq!("c4".parse(), Ok(Pile::Cell(3)));
+/// assert!("c0".parse::<Pile>().is_err());
+/// ```
+impl FromStr for Pile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |number: &str| match number.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(number - 1),
+            _ => Err(format!(
+                "expected a column, w, a free cell like c1 or f, found {:?}",
+                s
+            )),
+        };
+        match s.trim() {
+            "w" => Ok(Pile::Waste),
+            "f" => Ok(Pile::Foundation),
+            pile => match pile.strip_prefix('c') {
+                Some(cell) => number(cell).map(Pile::Cell),
+                None => number(pile).map(Pile::Column),
+            },
+        }
+    }
+}
+
+/// A single card played from the waste or a free cell, or to a free cell or the foundation, in
+/// variants that have them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Play {
+    /// The pile the card is taken from the top of.
+    pub from: Pile,
+    /// The pile the card is played onto.
+    pub to: Pile,
+}
+
+/// Writes the play as `from->to`, like `w->3`, `3->c1` or `3->f`.
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}->{}", self.from, self.to)
+    }
+}
+
+/// Parses a play written as `from->to`, like `w->3`, `3->c1` or `3->f`.
+///
+/// ```
+/// use spidertaire::game::*;
+///
+/// let parsed = "w->3".parse::<Play>();
+/// assert_eq!(parsed, Ok(Play { from: Pile::Waste, to: Pile::Column(2) }));
+/// assert_eq!(parsed.unwrap().to_string(), "w->3");
+/// assert_eq!("7->f".parse(), Ok(Play { from: Pile::Column(6), to: Pile::Foundation }));
+/// assert_eq!("c2->1".parse(), Ok(Play { from: Pile::Cell(1), to: Pile::Column(0) }));
+/// assert!("w3".parse::<Play>().is_err());
+/// ```
+impl FromStr for Play {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once("->")
+            .ok_or_else(|| format!("expected a play like w->3, 3->c1 or 3->f, found {:?}", s))?;
+        Ok(Play {
+            from: from.parse()?,
+            to: to.parse()?,
+        })
+    }
+}
+
+/// Something the player does to the game, in the order it was done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Action {
+    /// Moves a run of cards.
+    Move(Move),
+    /// Plays a single card from the waste or a free cell, or to a free cell or the foundation.
+    Play(Play),
+    /// Deals the next set from the stock.
+    Deal,
+    /// Undoes the last move or deal.
+    Undo,
+}
+
+/// Writes the action as a move like `3:4->7`, a play like `w->3`, `deal` or `undo`.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Move(legal_move) => legal_move.fmt(f),
+            Action::Play(play) => play.fmt(f),
+            Action::Deal => write!(f, "deal"),
+            Action::Undo => write!(f, "undo"),
+        }
+    }
+}
+
+/// Parses an action written as a move like `3:4->7`, a play like `w->3`, `deal` or `undo`.
+///
+/// ```
+/// use spidertaire::game::*;
+///
+/// assert_eq!("deal".parse(), Ok(Action::Deal));
+/// assert_eq!(" undo ".parse(), Ok(Action::Undo));
+/// assert_eq!("1:5->2".parse(), Ok(Action::Move(Move { from: 0, row: 4, to: 1 })));
+/// assert_eq!("w->f".parse(), Ok(Action::Play(Play { from: Pile::Waste, to: Pile::Foundation })));
+/// assert!("shuffle".parse::<Action>().is_err());
+/// ```
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "deal" => Ok(Action::Deal),
+            "undo" => Ok(Action::Undo),
+            s if s.contains(':') => s.parse().map(Action::Move),
+            s => s.parse().map(Action::Play).map_err(|_| {
+                format!(
+                    "expected a move like 3:4->7, a play like w->3, deal or undo, found {:?}",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl From<Action> for String {
+    fn from(action: Action) -> Self {
+        action.to_string()
+    }
+}
+
+impl TryFrom<String> for Action {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Whether a game is still being played, and how it ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// There are moves or deals remaining.
+    Playing,
+    /// Every run has been completed.
+    Won,
+    /// The stock is empty and no useful moves remain.
+    Lost,
+}
+
+impl Status {
+    /// Returns the display name of the status.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Status::Playing => "Playing",
+            Status::Won => "Won",
+            Status::Lost => "Lost",
+        }
+    }
+}
+
+/// The cards of a game between moves.
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    columns: Vec<Vec<TableauCard>>,
+    stock: Vec<Vec<Card>>,
+    waste: Vec<Card>,
+    cells: Vec<Option<Card>>,
+    foundation_piles: Vec<Vec<Card>>,
+    foundation: Vec<CardSuit>,
+}
+
+/// A game of Spider, or one of its variants.
+#[derive(Debug, Clone)]
+pub struct Game {
+    variant: Variant,
+    difficulty: Difficulty,
+    seed: u64,
+    columns: Vec<Vec<TableauCard>>,
+    stock: Vec<Vec<Card>>,
+    waste: Vec<Card>,
+    cells: Vec<Option<Card>>,
+    foundation_piles: Vec<Vec<Card>>,
+    foundation: Vec<CardSuit>,
+    actions: Vec<Action>,
+    history: Vec<Snapshot>,
+}
+
+impl Game {
+    /// Deals a new game of Spider. The same difficulty and seed always give the same deal.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::new(Difficulty::Easy, 42);
+    /// assert_eq!(game.columns()[0].len(), 6);
+    /// assert_eq!(game.columns()[9].len(), 5);
+    /// assert_eq!(game.stock().len(), 5);
+    /// assert!(game.columns().iter().all(|column| column.last().unwrap().face_up));
+    /// assert_eq!(game.columns(), Game::new(Difficulty::Easy, 42).columns());
+    /// ```
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self::with_variant(Variant::Spider, difficulty, seed)
+    }
+
+    /// Deals a new game of the given variant. The same variant, difficulty and seed always give the same deal.
+    /// Cards are dealt a row at a time across the columns the layout of the variant gives them to.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::with_variant(Variant::Spider, Difficulty::Hard, 7);
+    /// assert_eq!(game.columns().len(), 10);
+    /// assert_eq!(game.columns(), Game::new(Difficulty::Hard, 7).columns());
+    /// ```
+    pub fn with_variant(variant: Variant, difficulty: Difficulty, seed: u64) -> Self {
+        let rules = variant.rules();
+        let layout = rules.layout();
+        let mut cards = rules.shuffled_deck(difficulty, seed).cards.into_iter();
+        let mut columns = vec![Vec::new(); layout.len()];
+        let rows = layout.iter().map(|column| column.len()).max().unwrap_or(0);
+        for row in 0..rows {
+            for (column, column_layout) in columns.iter_mut().zip(&layout) {
+                if row < column_layout.len() {
+                    column.push(TableauCard {
+                        card: cards
+                            .next()
+                            .expect("The layout needs more cards than the deck has"),
+                        face_up: row >= column_layout.hidden,
+                    });
+                }
+            }
+        }
+        let stock = cards
+            .collect::<Vec<Card>>()
+            .chunks(rules.stock_set_size())
+            .map(<[Card]>::to_vec)
+            .collect();
+        let piles = if rules.builds_foundation() {
+            CardSuit::iter().len()
+        } else {
+            0
+        };
+        Game {
+            variant,
+            difficulty,
+            seed,
+            columns,
+            stock,
+            waste: Vec::new(),
+            cells: vec![None; rules.free_cells()],
+            foundation_piles: vec![Vec::new(); piles],
+            foundation: Vec::new(),
+            actions: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Gets the variant being played.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Gets the rules of the variant being played.
+    pub fn rules(&self) -> &'static dyn Rules {
+        self.variant.rules()
+    }
+
+    /// Gets the difficulty the game was dealt at.
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Gets the seed the game was dealt with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets the columns of the tableau, each ordered from the top of the column down.
+    pub fn columns(&self) -> &[Vec<TableauCard>] {
+        &self.columns
+    }
+
+    /// Gets the sets of cards remaining in the stock, in the order they are dealt.
+    /// Each set is dealt one card onto each of the first columns, or onto the waste in variants with one.
+    pub fn stock(&self) -> &[Vec<Card>] {
+        &self.stock
+    }
+
+    /// Gets the cards turned over from the stock onto the waste, with the top card, the only one
+    /// that can be played, last.
+    pub fn waste(&self) -> &[Card] {
+        &self.waste
+    }
+
+    /// Gets the free cells, each holding a single card or none.
+    /// There are no free cells in most variants.
+    pub fn free_cells(&self) -> &[Option<Card>] {
+        &self.cells
+    }
+
+    /// Gets the piles being built up on the foundation, one for each suit in the order of
+    /// [`CardSuit::iter`]. A pile is removed to the foundation as a completed run once it reaches
+    /// the king. There are no piles in variants that only collect completed runs.
+    pub fn foundation_piles(&self) -> &[Vec<Card>] {
+        &self.foundation_piles
+    }
+
+    /// Gets the suits of the runs that have been completed and removed from the tableau.
+    pub fn foundation(&self) -> &[CardSuit] {
+        &self.foundation
+    }
+
+    /// Gets the number of completed runs needed to win.
+    pub fn runs_to_win(&self) -> usize {
+        self.rules().runs_to_win()
+    }
+
+    /// Gets the number of moves, deals and undos made.
+    pub fn moves(&self) -> u32 {
+        self.actions.len() as u32
+    }
+
+    /// Gets every move, deal and undo made, in order. Playing them on a new game with the same
+    /// variant, difficulty and seed gives this game again.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Gets the points gained for each completed run: a hundred, and fifty more for every suit in play
+    /// beyond the first, or twenty five when runs are grouped by colour. Variants that ignore the
+    /// difficulty always score a hundred.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::{CardSuit, Difficulty, Suits};
+    ///
+    /// assert_eq!(Game::new(Difficulty::Easy, 0).run_score(), 100);
+    /// assert_eq!(Game::new(Difficulty::Hard, 0).run_score(), 250);
+    /// let suits = Suits::new([CardSuit::Spades, CardSuit::Hearts, CardSuit::Clubs]);
+    /// assert_eq!(Game::new(Difficulty::Custom { suits, by_colour: false }, 0).run_score(), 200);
+    /// assert_eq!(Game::new(Difficulty::Custom { suits, by_colour: true }, 0).run_score(), 150);
+    /// assert_eq!(Game::with_variant(Variant::Klondike, Difficulty::Hard, 0).run_score(), 100);
+    /// ```
+    pub fn run_score(&self) -> i32 {
+        if !self.rules().uses_difficulty() {
+            return RUN_SCORE;
+        }
+        let mut extra = EXTRA_SUIT_SCORE * (self.difficulty.suits().len() as i32 - 1);
+        if self.difficulty.groups_by_colour() {
+            extra /= 2;
+        }
+        RUN_SCORE + extra
+    }
+
+    /// Computes the score: one point is lost for every move, and [`Game::run_score`] gained for every completed run.
+    pub fn score(&self) -> i32 {
+        STARTING_SCORE - self.moves() as i32 + self.run_score() * self.foundation.len() as i32
+    }
+
+    /// Determines whether the game is still being played, and how it ended.
+    pub fn status(&self) -> Status {
+        if self.foundation.len() == self.runs_to_win() {
+            Status::Won
+        } else if self.stock_exhausted()
+            && self.hint().is_none()
+            && self.hint_play().is_none()
+            && !self.can_fill_free_cell()
+        {
+            Status::Lost
+        } else {
+            Status::Playing
+        }
+    }
+
+    /// Determines whether a card could be played into an empty free cell.
+    fn can_fill_free_cell(&self) -> bool {
+        self.cells.contains(&None) && self.columns.iter().any(|column| !column.is_empty())
+    }
+
+    /// Determines whether dealing can no longer help: the stock is empty or, in variants with a
+    /// waste, no card in the stock or the waste could be played even once it is on top.
+    fn stock_exhausted(&self) -> bool {
+        if !self.rules().has_waste() {
+            return self.stock.is_empty();
+        }
+        !self.stock.iter().flatten().chain(&self.waste).any(|card| {
+            self.foundation_accepts(*card)
+                || self.columns.iter().any(|column| {
+                    self.rules()
+                        .can_place(*card, column.last().map(|target| target.card))
+                })
+        })
+    }
+
+    /// Determines whether there is a move or deal to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Gets the longest run that can be moved to a column at once. In variants with free cells runs
+    /// are moved a card at a time through the empty free cells and columns, so at most
+    /// (free cells + 1) × 2^(empty columns) cards can be moved, not counting the column moved to.
+    /// There is no limit in other variants.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::with_variant(Variant::FreeCell, Difficulty::Easy, 1);
+    /// assert_eq!(game.max_run_length(0), 5);
+    /// assert_eq!(Game::new(Difficulty::Easy, 1).max_run_length(0), usize::MAX);
+    /// ```
+    pub fn max_run_length(&self, to: usize) -> usize {
+        if self.cells.is_empty() {
+            return usize::MAX;
+        }
+        let free_cells = self.cells.iter().filter(|cell| cell.is_none()).count();
+        let empty_columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(column, cards)| *column != to && cards.is_empty())
+            .count();
+        (free_cells + 1) << empty_columns
+    }
+
+    /// Gets the length of the run at the bottom of a column that can be moved together.
+    /// In Spider these are face up cards descending by one in a single suit.
+    pub fn movable_run(&self, column: usize) -> usize {
+        self.rules()
+            .movable_run(&self.columns[column], self.difficulty)
+    }
+
+    /// Checks a move is legal. In Spider the cards moved must be a face up run in a single suit,
+    /// and they must land on a card one higher, or in an empty column.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::new(Difficulty::Easy, 42);
+    /// assert!(game.check_move(Move { from: 0, row: 0, to: 1 }).is_err());
+    /// assert!(game.check_move(Move { from: 0, row: 5, to: 0 }).is_err());
+    /// ```
+    pub fn check_move(&self, legal_move: Move) -> Result<(), String> {
+        let Move { from, row, to } = legal_move;
+        let columns = self.columns.len();
+        if from >= columns || to >= columns {
+            return Err(format!("there are only {} columns", columns));
+        }
+        if from == to {
+            return Err(String::from("cards must move to a different column"));
+        }
+        let source = &self.columns[from];
+        if row >= source.len() {
+            return Err(format!(
+                "column {} has no card at row {}",
+                from + 1,
+                row + 1
+            ));
+        }
+        if row < source.len() - self.movable_run(from) {
+            return Err(format!(
+                "the cards from row {} of column {} are not {}",
+                row + 1,
+                from + 1,
+                self.rules().movable_run_description(self.difficulty)
+            ));
+        }
+        let limit = self.max_run_length(to);
+        if source.len() - row > limit {
+            return Err(format!(
+                "only {} cards can be moved to column {} at once",
+                limit,
+                to + 1
+            ));
+        }
+        self.check_place(source[row].card, to)
+    }
+
+    /// Checks a card can be placed at the bottom of a column, along with any cards on top of it.
+    fn check_place(&self, moved: Card, to: usize) -> Result<(), String> {
+        let target = self.columns[to].last().map(|target| target.card);
+        match target {
+            _ if self.rules().can_place(moved, target) => Ok(()),
+            None => Err(format!(
+                "{}{} cannot be placed in an empty column",
+                moved.value.as_str(),
+                moved.suit.as_str()
+            )),
+            Some(target) => Err(format!(
+                "{}{} cannot be placed on {}{}",
+                moved.value.as_str(),
+                moved.suit.as_str(),
+                target.value.as_str(),
+                target.suit.as_str()
+            )),
+        }
+    }
+
+    /// Determines whether a card can be played onto its pile on the foundation.
+    fn foundation_accepts(&self, card: Card) -> bool {
+        match self.foundation_piles.get(card.suit.index()) {
+            Some(pile) => match pile.last() {
+                Some(top) => top.value.previous() == Some(card.value),
+                None => card.value == CardValue::A,
+            },
+            None => false,
+        }
+    }
+
+    /// Gets the card in a free cell, if any.
+    fn free_cell(&self, cell: usize) -> Result<Option<Card>, String> {
+        self.cells
+            .get(cell)
+            .copied()
+            .ok_or_else(|| match self.cells.len() {
+                0 => String::from("there are no free cells"),
+                cells => format!("there are only {} free cells", cells),
+            })
+    }
+
+    /// Checks a play is legal. The top card of the waste or the card in a free cell can be played
+    /// onto a column, and the bottom card of a column into an empty free cell. Any of them can be
+    /// played onto the foundation, where it must be one higher than the top card of its suit's
+    /// pile, or an ace on an empty pile.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::new(Difficulty::Easy, 42);
+    /// let play = Play { from: Pile::Column(0), to: Pile::Foundation };
+    /// assert!(game.check_play(play).is_err());
+    ///
+    /// let mut game = Game::with_variant(Variant::Klondike, Difficulty::Easy, 42);
+    /// assert!(game.check_play(Play { from: Pile::Waste, to: Pile::Column(0) }).is_err());
+    /// game.deal().unwrap();
+    /// assert_eq!(game.waste().len(), 1);
+    /// ```
+    pub fn check_play(&self, play: Play) -> Result<(), String> {
+        let columns = self.columns.len();
+        let card = match play.from {
+            Pile::Waste => *self
+                .waste
+                .last()
+                .ok_or_else(|| String::from("the waste is empty"))?,
+            Pile::Column(from) if from >= columns => {
+                return Err(format!("there are only {} columns", columns))
+            }
+            Pile::Column(_) if matches!(play.to, Pile::Column(_)) => {
+                return Err(String::from(
+                    "runs are moved between columns with moves like 3:4->7",
+                ))
+            }
+            Pile::Column(from) => {
+                self.columns[from]
+                    .last()
+                    .ok_or_else(|| format!("column {} is empty", from + 1))?
+                    .card
+            }
+            Pile::Cell(cell) => self
+                .free_cell(cell)?
+                .ok_or_else(|| format!("free cell {} is empty", cell + 1))?,
+            Pile::Foundation => {
+                return Err(String::from(
+                    "cards cannot be taken back from the foundation",
+                ))
+            }
+        };
+        match play.to {
+            Pile::Column(to) if to >= columns => Err(format!("there are only {} columns", columns)),
+            Pile::Column(to) => self.check_place(card, to),
+            Pile::Waste => Err(String::from("cards cannot be played onto the waste")),
+            Pile::Cell(_) if matches!(play.from, Pile::Cell(_)) => {
+                Err(String::from("cards cannot be moved between free cells"))
+            }
+            Pile::Cell(cell) => match self.free_cell(cell)? {
+                Some(_) => Err(format!("free cell {} is full", cell + 1)),
+                None => Ok(()),
+            },
+            Pile::Foundation if self.foundation_piles.is_empty() => Err(String::from(
+                "only completed runs are collected on the foundation",
+            )),
+            Pile::Foundation if self.foundation_accepts(card) => Ok(()),
+            Pile::Foundation => Err(format!(
+                "{}{} cannot be played to the foundation",
+                card.value.as_str(),
+                card.suit.as_str()
+            )),
+        }
+    }
+
+    /// Finds every legal play, from the waste first, then from each free cell and then from each
+    /// column in turn. Plays to the foundation are listed first for each card.
+    pub fn legal_plays(&self) -> Vec<Play> {
+        let columns = || (0..self.columns.len()).map(Pile::Column);
+        let cells = || (0..self.cells.len()).map(Pile::Cell);
+        let sources = [Pile::Waste].into_iter().chain(cells()).chain(columns());
+        let mut legal_plays = Vec::new();
+        for from in sources {
+            let targets = [Pile::Foundation]
+                .into_iter()
+                .chain(columns())
+                .chain(cells());
+            for to in targets {
+                let play = Play { from, to };
+                if self.check_play(play).is_ok() {
+                    legal_plays.push(play);
+                }
+            }
+        }
+        legal_plays
+    }
+
+    /// Suggests a play, preferring plays to the foundation over playing the waste or a free cell
+    /// onto a column. Filling free cells, or emptying them into empty columns, is never suggested.
+    /// Returns `None` when there is no such play.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// assert_eq!(Game::new(Difficulty::Easy, 42).hint_play(), None);
+    /// let game = Game::with_variant(Variant::Klondike, Difficulty::Easy, 42);
+    /// if let Some(hint) = game.hint_play() {
+    ///     assert!(game.check_play(hint).is_ok());
+    /// }
+    /// ```
+    pub fn hint_play(&self) -> Option<Play> {
+        let legal_plays = self.legal_plays();
+        legal_plays
+            .iter()
+            .find(|play| play.to == Pile::Foundation)
+            .or_else(|| {
+                legal_plays.iter().find(|play| match (play.from, play.to) {
+                    (Pile::Waste, Pile::Column(_)) => true,
+                    (Pile::Cell(_), Pile::Column(to)) => !self.columns[to].is_empty(),
+                    _ => false,
+                })
+            })
+            .copied()
+    }
+
+    /// Finds every legal move, except moving a whole column to an empty one, which changes nothing.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut legal_moves = Vec::new();
+        for from in 0..self.columns.len() {
+            let length = self.columns[from].len();
+            for row in length - self.movable_run(from)..length {
+                for to in 0..self.columns.len() {
+                    let legal_move = Move { from, row, to };
+                    let pointless = row == 0 && self.columns[to].is_empty();
+                    if !pointless && self.check_move(legal_move).is_ok() {
+                        legal_moves.push(legal_move);
+                    }
+                }
+            }
+        }
+        legal_moves
+    }
+
+    /// Suggests a move that makes progress, preferring moves that turn over a face down card
+    /// or join cards of the same suit. Returns `None` when only moves that change nothing remain.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let game = Game::new(Difficulty::Easy, 42);
+    /// if let Some(hint) = game.hint() {
+    ///     assert!(game.check_move(hint).is_ok());
+    /// }
+    /// ```
+    pub fn hint(&self) -> Option<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter_map(|legal_move| {
+                let source = &self.columns[legal_move.from];
+                let moved = source[legal_move.row].card;
+                let above = legal_move.row.checked_sub(1).map(|index| source[index]);
+                let target = self.columns[legal_move.to].last();
+                let same_suit = target.is_some_and(|target| target.card.suit == moved.suit);
+                let already_placed =
+                    above.is_some_and(|above| above.face_up && moved.is_next_in_run(&above.card));
+                let in_suit =
+                    already_placed && above.is_some_and(|above| moved.is_next_in_suit(&above.card));
+                let priority = match above {
+                    Some(above) if !above.face_up => 3,
+                    None if target.is_some() => 2,
+                    _ if same_suit && !in_suit => 1,
+                    // moving a run from one card to another of equal standing goes nowhere
+                    _ if already_placed || target.is_none() => return None,
+                    _ => 0,
+                };
+                Some((priority, legal_move))
+            })
+            // the first of the best moves is suggested, so hints are stable
+            .rev()
+            .max_by_key(|(priority, _)| *priority)
+            .map(|(_, legal_move)| legal_move)
+    }
+
+    /// Takes a snapshot of the cards before a move or deal, so it can be undone.
+    fn record_history(&mut self) {
+        self.history.push(Snapshot {
+            columns: self.columns.clone(),
+            stock: self.stock.clone(),
+            waste: self.waste.clone(),
+            cells: self.cells.clone(),
+            foundation_piles: self.foundation_piles.clone(),
+            foundation: self.foundation.clone(),
+        });
+    }
+
+    /// Turns over the bottom card of every column, and moves any completed runs to the foundation.
+    fn settle(&mut self) {
+        let rules = self.rules();
+        let run_length = CardValue::iter().len();
+        for column in &mut self.columns {
+            if let Some(suit) = rules.completed_run(column) {
+                column.truncate(column.len() - run_length);
+                self.foundation.push(suit);
+            }
+            if let Some(last) = column.last_mut() {
+                last.face_up = true;
+            }
+        }
+        for pile in &mut self.foundation_piles {
+            if pile.len() == run_length {
+                self.foundation.push(pile[0].suit);
+                pile.clear();
+            }
+        }
+    }
+
+    /// Moves a run of cards, returning an error without changing the game if the move is not legal.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::new(Difficulty::Easy, 42);
+    /// if let Some(legal_move) = game.legal_moves().first().copied() {
+    ///     game.apply(legal_move).unwrap();
+    ///     assert_eq!(game.moves(), 1);
+    ///     assert_eq!(game.score(), 499);
+    /// }
+    /// assert!(game.apply(Move { from: 0, row: 0, to: 0 }).is_err());
+    /// ```
+    pub fn apply(&mut self, legal_move: Move) -> Result<(), String> {
+        self.check_move(legal_move)?;
+        self.record_history();
+        let run = self.columns[legal_move.from].split_off(legal_move.row);
+        self.columns[legal_move.to].extend(run);
+        self.actions.push(Action::Move(legal_move));
+        self.settle();
+        Ok(())
+    }
+
+    /// Plays a single card, returning an error without changing the game if the play is not legal.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::with_variant(Variant::Klondike, Difficulty::Easy, 42);
+    /// while let Some(play) = game.hint_play() {
+    ///     game.play(play).unwrap();
+    /// }
+    /// assert!(game.play(Play { from: Pile::Foundation, to: Pile::Column(0) }).is_err());
+    /// ```
+    pub fn play(&mut self, play: Play) -> Result<(), String> {
+        self.check_play(play)?;
+        self.record_history();
+        let card = match play.from {
+            Pile::Column(from) => self.columns[from]
+                .pop()
+                .map(|tableau_card| tableau_card.card),
+            Pile::Cell(cell) => self.cells[cell].take(),
+            _ => self.waste.pop(),
+        }
+        .expect("Legal plays have a card to play");
+        match play.to {
+            Pile::Column(to) => self.columns[to].push(TableauCard {
+                card,
+                face_up: true,
+            }),
+            Pile::Cell(cell) => self.cells[cell] = Some(card),
+            _ => {
+                self.foundation_piles[card.suit.index()].push(card);
+            }
+        }
+        self.actions.push(Action::Play(play));
+        self.settle();
+        Ok(())
+    }
+
+    /// Deals the next set from the stock, one card face up onto each of the first columns, or onto
+    /// the waste in variants with one. In Spider every column must have at least one card before dealing.
+    /// When the stock of a variant with a waste is empty, dealing turns the waste over to form it again.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::new(Difficulty::Easy, 42);
+    /// game.deal().unwrap();
+    /// assert_eq!(game.stock().len(), 4);
+    /// assert_eq!(game.columns()[9].len(), 6);
+    /// ```
+    pub fn deal(&mut self) -> Result<(), String> {
+        let rules = self.rules();
+        if rules.has_waste() && self.stock.is_empty() && !self.waste.is_empty() {
+            self.record_history();
+            self.stock = mem::take(&mut self.waste)
+                .chunks(rules.stock_set_size())
+                .map(<[Card]>::to_vec)
+                .collect();
+            self.actions.push(Action::Deal);
+            return Ok(());
+        }
+        if self.stock.is_empty() {
+            return Err(String::from("the stock is empty"));
+        }
+        if !rules.has_waste()
+            && rules.deal_needs_filled_columns()
+            && self.columns.iter().any(Vec::is_empty)
+        {
+            return Err(String::from("every column must have a card before dealing"));
+        }
+        self.record_history();
+        let set = self.stock.remove(0);
+        if rules.has_waste() {
+            self.waste.extend(set);
+        } else {
+            for (column, card) in self.columns.iter_mut().zip(set) {
+                column.push(TableauCard {
+                    card,
+                    face_up: true,
+                });
+            }
+        }
+        self.actions.push(Action::Deal);
+        self.settle();
+        Ok(())
+    }
+
+    /// Restores the game to how it was before the last move or deal. Undoing counts as a move.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::new(Difficulty::Easy, 42);
+    /// assert!(game.undo().is_err());
+    /// game.deal().unwrap();
+    /// game.undo().unwrap();
+    /// assert_eq!(game.stock().len(), 5);
+    /// assert_eq!(game.moves(), 2);
+    /// ```
+    pub fn undo(&mut self) -> Result<(), String> {
+        let snapshot = self
+            .history
+            .pop()
+            .ok_or_else(|| String::from("there is nothing to undo"))?;
+        self.columns = snapshot.columns;
+        self.stock = snapshot.stock;
+        self.waste = snapshot.waste;
+        self.cells = snapshot.cells;
+        self.foundation_piles = snapshot.foundation_piles;
+        self.foundation = snapshot.foundation;
+        self.actions.push(Action::Undo);
+        Ok(())
+    }
+
+    /// Performs a move, play, deal or undo, returning an error without changing the game if it is not legal.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::new(Difficulty::Easy, 42);
+    /// game.perform(Action::Deal).unwrap();
+    /// game.perform(Action::Undo).unwrap();
+    /// assert_eq!(game.actions(), [Action::Deal, Action::Undo]);
+    /// assert!(game.perform(Action::Undo).is_err());
+    /// ```
+    pub fn perform(&mut self, action: Action) -> Result<(), String> {
+        match action {
+            Action::Move(legal_move) => self.apply(legal_move),
+            Action::Play(play) => self.play(play),
+            Action::Deal => self.deal(),
+            Action::Undo => self.undo(),
+        }
+    }
+}
+
+/// Writes the tableau one row per line, with face down cards in brackets, followed by the top
+/// cards of the waste, the free cells and the foundation piles in variants with them, and the
+/// number of sets in the stock and runs in the foundation.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self.columns.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..rows {
+            let cells = self
+                .columns
+                .iter()
+                .map(|column| match column.get(row) {
+                    Some(tableau_card) => {
+                        let name = format!(
+                            "{}{}",
+                            tableau_card.card.value.as_str(),
+                            tableau_card.card.suit.as_str()
+                        );
+                        if tableau_card.face_up {
+                            format!(" {:<3} ", name)
+                        } else {
+                            format!("[{:<3}]", name)
+                        }
+                    }
+                    None => String::from("     "),
+                })
+                .collect::<Vec<String>>();
+            writeln!(f, "{}", cells.join(" ").trim_end())?;
+        }
+        let names = |cards: &mut dyn Iterator<Item = Option<&Card>>| {
+            cards
+                .map(|card| match card {
+                    Some(card) => format!("{}{}", card.value.as_str(), card.suit.as_str()),
+                    None => String::from("--"),
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        if self.rules().has_waste() {
+            writeln!(f, "Waste: {}", names(&mut iter::once(self.waste.last())))?;
+        }
+        if !self.cells.is_empty() {
+            writeln!(
+                f,
+                "Free cells: {}",
+                names(&mut self.cells.iter().map(Option::as_ref))
+            )?;
+        }
+        if !self.foundation_piles.is_empty() {
+            let mut tops = self.foundation_piles.iter().map(|pile| pile.last());
+            writeln!(f, "Foundation piles: {}", names(&mut tops))?;
+        }
+        write!(
+            f,
+            "Stock: {} sets, foundation: {} of {} runs",
+            self.stock.len(),
+            self.foundation.len(),
+            self.runs_to_win()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Suits;
+
+    fn card(value: CardValue, suit: CardSuit) -> TableauCard {
+        TableauCard {
+            card: Card { value, suit },
+            face_up: true,
+        }
+    }
+
+    /// Builds a game with the given columns, padding the rest with a single face down card.
+    fn game_with(columns: Vec<Vec<TableauCard>>, stock: usize) -> Game {
+        let mut game = Game::new(Difficulty::Easy, 0);
+        let filler = TableauCard {
+            face_up: false,
+            ..card(CardValue::K, CardSuit::Spades)
+        };
+        game.columns = columns;
+        game.columns.resize(10, vec![filler]);
+        game.stock.truncate(stock);
+        game
+    }
+
+    #[test]
+    fn deals_spider_a_row_at_a_time() {
+        let game = Game::new(Difficulty::Hard, 42);
+        let mut deck = Difficulty::Hard.deck();
+        deck.shuffle_seeded(42);
+        for (pos, card) in deck.cards.iter().take(54).enumerate() {
+            let tableau_card = game.columns()[pos % 10][pos / 10];
+            assert_eq!(tableau_card.card, *card);
+            assert_eq!(tableau_card.face_up, pos >= 44);
+        }
+        assert_eq!(game.stock()[0], deck.cards[54..64]);
+    }
+
+    #[test]
+    fn deals_spiderette_into_seven_columns() {
+        let mut game = Game::with_variant(Variant::Spiderette, Difficulty::Hard, 42);
+        let lengths = game.columns().iter().map(Vec::len).collect::<Vec<usize>>();
+        assert_eq!(lengths, [1, 2, 3, 4, 5, 6, 7]);
+        assert!(game.columns()[6][5..]
+            .iter()
+            .map(|tableau_card| tableau_card.face_up)
+            .eq([false, true]));
+        let sets = game.stock().iter().map(Vec::len).collect::<Vec<usize>>();
+        assert_eq!(sets, [7, 7, 7, 3]);
+        for _ in 0..4 {
+            game.deal().unwrap();
+        }
+        let lengths = game.columns().iter().map(Vec::len).collect::<Vec<usize>>();
+        assert_eq!(lengths, [5, 6, 7, 7, 8, 9, 10]);
+        assert_eq!(game.runs_to_win(), 4);
+    }
+
+    #[test]
+    fn moves_any_face_up_stack_in_scorpion() {
+        let mut game = Game::with_variant(Variant::Scorpion, Difficulty::Hard, 42);
+        let hidden = TableauCard {
+            face_up: false,
+            ..card(CardValue::Two, CardSuit::Clubs)
+        };
+        game.columns = vec![
+            vec![
+                hidden,
+                card(CardValue::Nine, CardSuit::Hearts),
+                card(CardValue::Four, CardSuit::Spades),
+                card(CardValue::J, CardSuit::Clubs),
+            ],
+            vec![card(CardValue::Ten, CardSuit::Hearts)],
+            vec![card(CardValue::Ten, CardSuit::Spades)],
+            vec![card(CardValue::K, CardSuit::Diamonds)],
+            Vec::new(),
+        ];
+        let run = |from, row, to| Move { from, row, to };
+        assert!(game.check_move(run(0, 0, 1)).is_err());
+        assert!(game.check_move(run(0, 1, 2)).is_err());
+        assert!(game.check_move(run(0, 1, 4)).is_err());
+        game.apply(run(0, 1, 1)).unwrap();
+        assert_eq!(game.columns()[1].len(), 4);
+        assert!(game.columns()[0][0].face_up);
+        game.apply(run(3, 0, 4)).unwrap();
+        game.deal().unwrap();
+        assert!(game.stock().is_empty());
+        assert_eq!(game.columns()[2].len(), 2);
+    }
+
+    #[test]
+    fn builds_the_foundation_from_the_waste_in_klondike() {
+        let mut game = Game::with_variant(Variant::Klondike, Difficulty::Hard, 42);
+        game.columns = vec![
+            vec![card(CardValue::Two, CardSuit::Hearts)],
+            vec![card(CardValue::Three, CardSuit::Spades)],
+            vec![card(CardValue::Q, CardSuit::Hearts)],
+        ];
+        game.stock = vec![
+            vec![Card {
+                value: CardValue::A,
+                suit: CardSuit::Hearts,
+            }],
+            vec![Card {
+                value: CardValue::K,
+                suit: CardSuit::Clubs,
+            }],
+        ];
+        let play = |from, to| Play { from, to };
+        assert!(game
+            .check_move(Move {
+                from: 0,
+                row: 0,
+                to: 1
+            })
+            .is_ok());
+        assert!(game
+            .check_play(play(Pile::Column(0), Pile::Foundation))
+            .is_err());
+        game.deal().unwrap();
+        game.play(play(Pile::Waste, Pile::Foundation)).unwrap();
+        game.play(play(Pile::Column(0), Pile::Foundation)).unwrap();
+        assert_eq!(game.foundation_piles()[0].len(), 2);
+        assert!(game.columns()[0].is_empty());
+        game.deal().unwrap();
+        assert!(game.check_play(play(Pile::Waste, Pile::Column(1))).is_err());
+        game.play(play(Pile::Waste, Pile::Column(0))).unwrap();
+        assert!(game.deal().is_err());
+        game.apply(Move {
+            from: 2,
+            row: 0,
+            to: 0,
+        })
+        .unwrap();
+        assert_eq!(game.movable_run(0), 2);
+        game.undo().unwrap();
+        game.undo().unwrap();
+        game.deal().unwrap();
+        assert_eq!(game.stock().len(), 1);
+        assert!(game.waste().is_empty());
+    }
+
+    #[test]
+    fn limits_runs_by_free_cells_and_empty_columns_in_freecell() {
+        let mut game = Game::with_variant(Variant::FreeCell, Difficulty::Easy, 1);
+        game.columns = vec![
+            vec![
+                card(CardValue::Nine, CardSuit::Spades),
+                card(CardValue::Eight, CardSuit::Hearts),
+                card(CardValue::Seven, CardSuit::Clubs),
+                card(CardValue::Six, CardSuit::Diamonds),
+            ],
+            vec![card(CardValue::Ten, CardSuit::Hearts)],
+            vec![
+                card(CardValue::Two, CardSuit::Spades),
+                card(CardValue::Three, CardSuit::Clubs),
+                card(CardValue::Four, CardSuit::Hearts),
+                card(CardValue::Five, CardSuit::Clubs),
+            ],
+        ];
+        let play = |from, to| Play { from, to };
+        for cell in 0..4 {
+            game.play(play(Pile::Column(2), Pile::Cell(cell))).unwrap();
+        }
+        assert!(game
+            .check_play(play(Pile::Column(0), Pile::Cell(0)))
+            .is_err());
+        assert!(game.check_play(play(Pile::Cell(0), Pile::Cell(1))).is_err());
+        assert_eq!(game.max_run_length(1), 2);
+        assert_eq!(game.max_run_length(2), 1);
+        assert_eq!(
+            game.check_move(Move {
+                from: 0,
+                row: 0,
+                to: 1
+            })
+            .unwrap_err(),
+            "only 2 cards can be moved to column 2 at once"
+        );
+        game.play(play(Pile::Cell(0), Pile::Column(0))).unwrap();
+        game.play(play(Pile::Cell(1), Pile::Column(0))).unwrap();
+        assert_eq!(game.max_run_length(1), 6);
+        game.apply(Move {
+            from: 0,
+            row: 0,
+            to: 1,
+        })
+        .unwrap();
+        assert_eq!(game.columns()[1].len(), 7);
+        assert_eq!(game.free_cells()[..2], [None, None]);
+        game.undo().unwrap();
+        assert_eq!(game.columns()[0].len(), 6);
+    }
+
+    #[test]
+    fn moves_only_single_suit_runs() {
+        let game = game_with(
+            vec![
+                vec![
+                    card(CardValue::Nine, CardSuit::Hearts),
+                    card(CardValue::Eight, CardSuit::Spades),
+                    card(CardValue::Seven, CardSuit::Spades),
+                ],
+                vec![card(CardValue::Nine, CardSuit::Spades)],
+                vec![card(CardValue::Ten, CardSuit::Spades)],
+            ],
+            5,
+        );
+        assert!(game
+            .check_move(Move {
+                from: 0,
+                row: 1,
+                to: 1
+            })
+            .is_ok());
+        assert!(game
+            .check_move(Move {
+                from: 0,
+                row: 0,
+                to: 2
+            })
+            .is_err());
+        assert!(game
+            .check_move(Move {
+                from: 0,
+                row: 2,
+                to: 1
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn moves_runs_of_one_colour_when_grouped_by_colour() {
+        let mut game = game_with(
+            vec![
+                vec![
+                    card(CardValue::Ten, CardSuit::Hearts),
+                    card(CardValue::Eight, CardSuit::Spades),
+                    card(CardValue::Seven, CardSuit::Clubs),
+                ],
+                vec![card(CardValue::Nine, CardSuit::Diamonds)],
+            ],
+            5,
+        );
+        let run = Move {
+            from: 0,
+            row: 1,
+            to: 1,
+        };
+        assert!(game.check_move(run).is_err());
+        game.difficulty = Difficulty::Custom {
+            suits: Suits::all(),
+            by_colour: true,
+        };
+        assert_eq!(game.movable_run(0), 2);
+        assert!(game.check_move(run).is_ok());
+        assert!(game.check_move(Move { row: 0, ..run }).is_err());
+    }
+
+    #[test]
+    fn collects_completed_runs_and_reveals_cards() {
+        let hidden = TableauCard {
+            face_up: false,
+            ..card(CardValue::Four, CardSuit::Spades)
+        };
+        let mut run = vec![hidden];
+        run.extend(
+            CardValue::iter()
+                .rev()
+                .skip(1)
+                .map(|value| card(value, CardSuit::Spades)),
+        );
+        let mut game = game_with(vec![vec![card(CardValue::K, CardSuit::Spades)], run], 5);
+        game.apply(Move {
+            from: 1,
+            row: 1,
+            to: 0,
+        })
+        .unwrap();
+        assert!(game.columns()[0].is_empty());
+        assert_eq!(
+            game.columns()[1],
+            vec![card(CardValue::Four, CardSuit::Spades)]
+        );
+        assert_eq!(game.foundation(), [CardSuit::Spades]);
+        assert_eq!(game.score(), 599);
+        game.undo().unwrap();
+        assert!(game.foundation().is_empty());
+        assert_eq!(game.columns()[1].len(), 13);
+    }
+
+    #[test]
+    fn deals_only_onto_filled_columns() {
+        let mut game = game_with(vec![Vec::new()], 1);
+        assert!(game.deal().is_err());
+        game.columns[0].push(card(CardValue::A, CardSuit::Spades));
+        game.deal().unwrap();
+        assert!(game.deal().is_err());
+    }
+
+    #[test]
+    fn lost_when_no_useful_moves_remain() {
+        let game = game_with(vec![vec![card(CardValue::A, CardSuit::Spades)]], 0);
+        assert_eq!(game.status(), Status::Lost);
+        let game = game_with(vec![vec![card(CardValue::A, CardSuit::Spades)]], 1);
+        assert_eq!(game.status(), Status::Playing);
+    }
+}
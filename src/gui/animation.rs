@@ -0,0 +1,170 @@
+//! Tweens moving cards around the table and turning them face up.
+
+use bevy::prelude::*;
+
+/// The time taken to move a card to a new position, in seconds.
+const MOVE_DURATION: f32 = 0.2;
+/// The time between each card of a stock set leaving the stock, in seconds.
+const DEAL_INTERVAL: f32 = 0.06;
+/// The time taken to turn a card face up, in seconds.
+const FLIP_DURATION: f32 = 0.15;
+/// The time taken to move a card of a completed run to the foundation, in seconds.
+const COLLECT_DURATION: f32 = 0.3;
+/// The time between each card of a completed run leaving the grid, in seconds.
+const COLLECT_INTERVAL: f32 = 0.03;
+/// The distance moving cards are raised towards the camera, so they pass over resting cards.
+const LIFT_Z: f32 = 100.0;
+
+/// A plugin advancing card tweens every frame.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationSettings>()
+            .add_system(animate_moves)
+            .add_system(animate_flips);
+    }
+}
+
+/// A resource controlling how cards are animated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationSettings {
+    /// Whether cards are animated. When disabled, cards jump straight to their destination.
+    pub enabled: bool,
+    /// A multiplier applied to the speed of every animation.
+    pub speed: f32,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        AnimationSettings {
+            enabled: true,
+            speed: 1.0,
+        }
+    }
+}
+
+impl AnimationSettings {
+    /// Creates a timer lasting the given number of seconds, adjusted for the animation speed.
+    fn timer(&self, seconds: f32) -> Timer {
+        Timer::from_seconds(seconds / self.speed.max(f32::EPSILON), false)
+    }
+
+    /// Creates a tween moving a card to a new position in the grid.
+    pub fn move_tween(&self, from: Vec3, to: Vec3) -> MoveTween {
+        MoveTween {
+            from,
+            to,
+            delay: self.timer(0.0),
+            timer: self.timer(MOVE_DURATION),
+            despawn: false,
+        }
+    }
+
+    /// Creates a tween moving a card from the stock to the grid.
+    /// The index is the column the card is dealt to, so cards leave the stock one at a time.
+    pub fn deal_tween(&self, from: Vec3, to: Vec3, index: usize) -> MoveTween {
+        MoveTween {
+            delay: self.timer(DEAL_INTERVAL * index as f32),
+            ..self.move_tween(from, to)
+        }
+    }
+
+    /// Creates a tween moving a card of a completed run to the foundation, then despawning it.
+    /// The index is the card's position in the run, so the cards leave the grid one at a time.
+    pub fn collect_tween(&self, from: Vec3, to: Vec3, index: usize) -> MoveTween {
+        MoveTween {
+            from,
+            to,
+            delay: self.timer(COLLECT_INTERVAL * index as f32),
+            timer: self.timer(COLLECT_DURATION),
+            despawn: true,
+        }
+    }
+
+    /// Creates a tween turning a card face up.
+    pub fn flip(&self) -> Flip {
+        Flip {
+            timer: self.timer(FLIP_DURATION),
+        }
+    }
+}
+
+/// A component moving an entity in a straight line after an optional delay.
+#[derive(Component, Debug, Clone)]
+pub struct MoveTween {
+    from: Vec3,
+    to: Vec3,
+    delay: Timer,
+    timer: Timer,
+    despawn: bool,
+}
+
+impl MoveTween {
+    /// Gets the position the entity will be at when the tween finishes.
+    pub fn target(&self) -> Vec3 {
+        self.to
+    }
+
+    /// Restarts the tween from the given position towards a new target, keeping any remaining delay.
+    pub fn retarget(&mut self, from: Vec3, to: Vec3) {
+        self.from = from;
+        self.to = to;
+        self.timer.reset();
+    }
+}
+
+/// A component turning a card face up by stretching it horizontally from its centre.
+#[derive(Component, Debug, Clone)]
+pub struct Flip {
+    timer: Timer,
+}
+
+/// Eases a linear progress value in and out.
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn animate_moves(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+    mut query: Query<(Entity, &mut MoveTween, &mut Transform)>,
+) {
+    for (e, mut tween, mut transform) in query.iter_mut() {
+        if settings.enabled {
+            tween.delay.tick(time.delta());
+            if !tween.delay.finished() {
+                continue;
+            }
+            tween.timer.tick(time.delta());
+        }
+        if settings.enabled && !tween.timer.finished() {
+            let mut translation = tween.from.lerp(tween.to, smoothstep(tween.timer.percent()));
+            translation.z = tween.from.z.max(tween.to.z) + LIFT_Z;
+            transform.translation = translation;
+        } else if tween.despawn {
+            commands.entity(e).despawn_recursive();
+        } else {
+            transform.translation = tween.to;
+            commands.entity(e).remove::<MoveTween>();
+        }
+    }
+}
+
+fn animate_flips(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+    mut query: Query<(Entity, &mut Flip, &mut Transform)>,
+) {
+    for (e, mut flip, mut transform) in query.iter_mut() {
+        flip.timer.tick(time.delta());
+        if settings.enabled && !flip.timer.finished() {
+            transform.scale.x = smoothstep(flip.timer.percent());
+        } else {
+            transform.scale.x = 1.0;
+            commands.entity(e).remove::<Flip>();
+        }
+    }
+}
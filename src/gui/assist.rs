@@ -0,0 +1,175 @@
+//! An optional assist mode drawing the legal moves over the grid.
+
+use bevy::prelude::*;
+
+use super::layout::Layout;
+use super::picking::{pick, Pickable};
+use super::selection::Selection;
+use super::touch::Dragged;
+use crate::{GridPosition, LegalMoves, Shown};
+
+/// The distance the outline extends past the edges of a card with a legal move.
+const OUTLINE_WIDTH: f32 = 3.0;
+/// The depth of an outline relative to its card. The outline sits just behind its card and above
+/// the card before it, and stays in front of the camera's near plane for the top row.
+const OUTLINE_Z: f32 = -0.05;
+/// The depth of the tint over destination columns, above every resting card.
+const COLUMN_Z: f32 = 50.0;
+const OUTLINE_COLOR: Color = Color::rgb(0.3, 0.85, 0.35);
+const RUN_COLOR: Color = Color::rgba(0.3, 0.85, 0.35, 0.35);
+const COLUMN_COLOR: Color = Color::rgba(0.3, 0.85, 0.35, 0.2);
+
+/// A plugin drawing the legal moves when [`AssistSettings`] is enabled.
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssistSettings>()
+            .add_system(draw_assist_highlights);
+    }
+}
+
+/// A resource controlling the assist mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AssistSettings {
+    /// Whether legal moves, the movable run under the cursor and valid destinations are highlighted.
+    pub enabled: bool,
+}
+
+/// A component marking a sprite drawn by the assist mode.
+#[derive(Component)]
+struct AssistHighlight;
+
+/// A sprite drawn by the assist mode, described by its colour, centre and size.
+type HighlightSprite = (Color, Vec3, Vec2);
+
+/// Finds the card under the mouse cursor, or the card focused by the selection cursor.
+fn find_hovered(
+    windows: &Windows,
+    layout: &Layout,
+    selection: &Selection,
+    shown: &Query<&GridPosition, With<Shown>>,
+) -> Option<GridPosition> {
+    if selection.active {
+        return Some(selection.focus);
+    }
+    let cursor = windows.get_primary()?.cursor_position()?;
+    pick(
+        shown.iter().map(|position| Pickable {
+            item: *position,
+            bounds: layout.card_bounds(layout.grid_center(position)),
+            depth: position.y as f32,
+        }),
+        layout.world_position(cursor),
+    )
+}
+
+/// Works out the sprites to draw for the current legal moves, cursor and held run.
+fn assist_sprites(
+    layout: &Layout,
+    legal_moves: &LegalMoves,
+    hovered: Option<GridPosition>,
+    held: Option<GridPosition>,
+    shown: &Query<&GridPosition, With<Shown>>,
+) -> Vec<HighlightSprite> {
+    let mut sprites = Vec::new();
+    let mut movable = legal_moves
+        .iter()
+        .map(|legal_move| legal_move.0)
+        .collect::<Vec<GridPosition>>();
+    movable.sort_by_key(|position| (position.x, position.y));
+    movable.dedup();
+    for position in &movable {
+        sprites.push((
+            OUTLINE_COLOR,
+            layout
+                .grid_center(position)
+                .extend(position.y as f32 + OUTLINE_Z),
+            Vec2::splat(layout.card_size + OUTLINE_WIDTH * 2.0),
+        ));
+    }
+
+    if let Some(from) = held.or(hovered).filter(|from| movable.contains(from)) {
+        let mut run = shown
+            .iter()
+            .filter(|position| position.x == from.x && position.y >= from.y)
+            .copied()
+            .collect::<Vec<GridPosition>>();
+        run.sort_by_key(|position| position.y);
+        for position in run {
+            sprites.push((
+                RUN_COLOR,
+                layout
+                    .grid_center(&position)
+                    .extend(position.y as f32 + 0.5),
+                Vec2::splat(layout.card_size),
+            ));
+        }
+    }
+
+    if let Some(from) = held {
+        let mut columns = legal_moves
+            .iter()
+            .filter(|legal_move| legal_move.0 == from)
+            .map(|legal_move| legal_move.1.x)
+            .collect::<Vec<u8>>();
+        columns.sort_unstable();
+        columns.dedup();
+        let bottom = layout.window_size.y / -2.0 + layout.gap;
+        for x in columns {
+            let top = layout.grid_corner.y;
+            let center_x = layout.grid_center(&GridPosition { x, y: 0 }).x;
+            sprites.push((
+                COLUMN_COLOR,
+                Vec3::new(center_x, (top + bottom) / 2.0, COLUMN_Z),
+                Vec2::new(layout.card_size, top - bottom),
+            ));
+        }
+    }
+    sprites
+}
+
+/// Respawns the assist sprites whenever the highlighted moves change.
+#[allow(clippy::too_many_arguments)]
+fn draw_assist_highlights(
+    mut commands: Commands,
+    settings: Res<AssistSettings>,
+    layout: Res<Layout>,
+    legal_moves: Res<LegalMoves>,
+    selection: Res<Selection>,
+    windows: Res<Windows>,
+    mut drawn: Local<Vec<HighlightSprite>>,
+    shown: Query<&GridPosition, With<Shown>>,
+    dragged: Query<&GridPosition, With<Dragged>>,
+    highlights: Query<Entity, With<AssistHighlight>>,
+) {
+    let sprites = if settings.enabled {
+        let hovered = find_hovered(&windows, &layout, &selection, &shown);
+        let held = selection
+            .held
+            .or_else(|| dragged.iter().min_by_key(|position| position.y).copied());
+        assist_sprites(&layout, &legal_moves, hovered, held, &shown)
+    } else {
+        Vec::new()
+    };
+    if *drawn == sprites {
+        return;
+    }
+    for e in highlights.iter() {
+        commands.entity(e).despawn();
+    }
+    for (color, translation, size) in &sprites {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: *color,
+                    custom_size: Some(*size),
+                    ..default()
+                },
+                transform: Transform::from_translation(*translation),
+                ..default()
+            })
+            .insert(AssistHighlight);
+    }
+    *drawn = sprites;
+}
@@ -0,0 +1,216 @@
+//! Gamepad controls, sending the same selection inputs as the keyboard.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::selection::SelectionInput;
+use crate::{AppState, PlayingSystem};
+
+/// How far a stick must be pushed along an axis before it moves the selection.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// A plugin translating gamepad buttons and sticks into [`SelectionInput`] events.
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::Playing).with_system(
+                gamepad_selection_input
+                    .label(PlayingSystem::Input)
+                    .after(PlayingSystem::GameOver),
+            ),
+        );
+    }
+}
+
+/// Gets the selection input sent when the given button is pressed.
+fn button_input(button_type: GamepadButtonType) -> Option<SelectionInput> {
+    match button_type {
+        GamepadButtonType::DPadLeft => Some(SelectionInput::Left),
+        GamepadButtonType::DPadRight => Some(SelectionInput::Right),
+        GamepadButtonType::DPadUp => Some(SelectionInput::Up),
+        GamepadButtonType::DPadDown => Some(SelectionInput::Down),
+        GamepadButtonType::South => Some(SelectionInput::Activate),
+        GamepadButtonType::West => Some(SelectionInput::Deal),
+        GamepadButtonType::North => Some(SelectionInput::Undo),
+        GamepadButtonType::East => Some(SelectionInput::Hint),
+        GamepadButtonType::LeftTrigger => Some(SelectionInput::PlayWaste),
+        GamepadButtonType::RightTrigger => Some(SelectionInput::PlayToFoundation),
+        GamepadButtonType::LeftTrigger2 => Some(SelectionInput::PlayCell(None)),
+        GamepadButtonType::RightTrigger2 => Some(SelectionInput::PlayToFreeCell),
+        _ => None,
+    }
+}
+
+/// Gets the selection input for a stick position, following whichever axis is pushed furthest.
+fn stick_input(x: f32, y: f32) -> Option<SelectionInput> {
+    if x.abs().max(y.abs()) < STICK_THRESHOLD {
+        None
+    } else if x.abs() >= y.abs() {
+        Some(if x < 0.0 {
+            SelectionInput::Left
+        } else {
+            SelectionInput::Right
+        })
+    } else {
+        Some(if y < 0.0 {
+            SelectionInput::Down
+        } else {
+            SelectionInput::Up
+        })
+    }
+}
+
+/// Sends a selection input for each button press, and each time a left stick is pushed in a new direction.
+fn gamepad_selection_input(
+    gamepads: Res<Gamepads>,
+    button_inputs: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut stick_directions: Local<HashMap<Gamepad, SelectionInput>>,
+    mut inputs: EventWriter<SelectionInput>,
+) {
+    for button in button_inputs.get_just_pressed() {
+        if let Some(input) = button_input(button.1) {
+            inputs.send(input);
+        }
+    }
+    stick_directions.retain(|gamepad, _| gamepads.contains(gamepad));
+    for gamepad in gamepads.iter() {
+        let x = axes
+            .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default();
+        let y = axes
+            .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or_default();
+        match stick_input(x, y) {
+            Some(input) if stick_directions.get(gamepad) != Some(&input) => {
+                stick_directions.insert(*gamepad, input);
+                inputs.send(input);
+            }
+            Some(_) => {}
+            None => {
+                stick_directions.remove(gamepad);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::input::gamepad::{GamepadEventRaw, GamepadEventType};
+    use bevy::input::InputPlugin;
+
+    use super::*;
+
+    const GAMEPAD: Gamepad = Gamepad(0);
+
+    /// Creates a headless app running only the input plugin and the gamepad system.
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(InputPlugin)
+            .add_event::<SelectionInput>()
+            .add_system(gamepad_selection_input);
+        send(&mut app, GamepadEventType::Connected);
+        app.update();
+        app
+    }
+
+    /// Sends a synthetic gamepad event, as the gilrs backend would.
+    fn send(app: &mut App, event_type: GamepadEventType) {
+        app.world
+            .resource_mut::<Events<GamepadEventRaw>>()
+            .send(GamepadEventRaw(GAMEPAD, event_type));
+    }
+
+    /// Runs a frame after sending the given events, returning the selection inputs sent during it.
+    fn update(app: &mut App, event_types: &[GamepadEventType]) -> Vec<SelectionInput> {
+        for event_type in event_types {
+            send(app, event_type.clone());
+        }
+        app.update();
+        app.world
+            .resource_mut::<Events<SelectionInput>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn buttons_send_selection_inputs() {
+        let mut app = app();
+        for (button_type, input) in [
+            (GamepadButtonType::DPadLeft, SelectionInput::Left),
+            (GamepadButtonType::DPadUp, SelectionInput::Up),
+            (GamepadButtonType::South, SelectionInput::Activate),
+            (GamepadButtonType::West, SelectionInput::Deal),
+            (GamepadButtonType::North, SelectionInput::Undo),
+            (GamepadButtonType::East, SelectionInput::Hint),
+            (GamepadButtonType::LeftTrigger, SelectionInput::PlayWaste),
+            (
+                GamepadButtonType::RightTrigger,
+                SelectionInput::PlayToFoundation,
+            ),
+            (
+                GamepadButtonType::LeftTrigger2,
+                SelectionInput::PlayCell(None),
+            ),
+            (
+                GamepadButtonType::RightTrigger2,
+                SelectionInput::PlayToFreeCell,
+            ),
+        ] {
+            let pressed = update(
+                &mut app,
+                &[GamepadEventType::ButtonChanged(button_type, 1.0)],
+            );
+            assert_eq!(pressed, vec![input]);
+            update(
+                &mut app,
+                &[GamepadEventType::ButtonChanged(button_type, 0.0)],
+            );
+        }
+    }
+
+    #[test]
+    fn held_button_sends_one_input() {
+        let mut app = app();
+        let press = GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.0);
+        assert_eq!(update(&mut app, &[press]), vec![SelectionInput::Activate]);
+        assert_eq!(update(&mut app, &[]), vec![]);
+        assert_eq!(update(&mut app, &[]), vec![]);
+    }
+
+    #[test]
+    fn unmapped_buttons_send_nothing() {
+        let mut app = app();
+        let press = GamepadEventType::ButtonChanged(GamepadButtonType::Select, 1.0);
+        assert_eq!(update(&mut app, &[press]), vec![]);
+    }
+
+    #[test]
+    fn stick_sends_one_input_per_push() {
+        let mut app = app();
+        let right = GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, 1.0);
+        let centre = GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, 0.0);
+        let left = GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, -1.0);
+        assert_eq!(
+            update(&mut app, &[right.clone()]),
+            vec![SelectionInput::Right]
+        );
+        assert_eq!(update(&mut app, &[]), vec![]);
+        assert_eq!(update(&mut app, &[centre]), vec![]);
+        assert_eq!(update(&mut app, &[right]), vec![SelectionInput::Right]);
+        assert_eq!(update(&mut app, &[left]), vec![SelectionInput::Left]);
+    }
+
+    #[test]
+    fn stick_follows_furthest_axis() {
+        assert_eq!(stick_input(0.2, 0.3), None);
+        assert_eq!(stick_input(0.6, -0.9), Some(SelectionInput::Down));
+        assert_eq!(stick_input(-0.9, 0.6), Some(SelectionInput::Left));
+        assert_eq!(stick_input(0.0, 0.7), Some(SelectionInput::Up));
+    }
+}
@@ -0,0 +1,178 @@
+//! Hints pointing out a legal move or play, shown on request for a short time.
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Pile, Play};
+
+use super::layout::Layout;
+use crate::{grid_move, AppState, GameAction, GridPosition, PlayingSystem};
+
+/// The time a hint stays on screen, in seconds.
+const HINT_DURATION: f32 = 1.5;
+/// The distance the hint extends past the edges of the cards it points out.
+const HINT_WIDTH: f32 = 4.0;
+const HINT_COLOR: Color = Color::rgba(0.2, 0.9, 0.3, 0.45);
+
+/// A plugin showing a legal move in response to [`HintRequest`] events.
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hint>()
+            .add_event::<HintRequest>()
+            .add_startup_system(spawn_hint_highlights)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(clear_hint))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing).with_system(
+                    show_hint
+                        .after(PlayingSystem::Selection)
+                        .before(PlayingSystem::Action),
+                ),
+            )
+            .add_system(draw_hint);
+    }
+}
+
+/// An event asking for a legal move to be pointed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HintRequest;
+
+/// A resource holding the move currently pointed out, and how long it remains on screen.
+#[derive(Debug, Default)]
+pub struct Hint {
+    /// The first card of the run or the card to move, and where it can be moved to.
+    pub ends: Option<(HintEnd, HintEnd)>,
+    timer: Timer,
+}
+
+/// One end of a hinted move or play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintEnd {
+    /// A card in the grid, or the space below the bottom card of a column.
+    Grid(GridPosition),
+    /// The top card of the waste.
+    Waste,
+    /// The free cell at the given index in [`Game::free_cells`].
+    Cell(usize),
+    /// The foundation pile at the given index in [`Game::foundation_piles`].
+    Foundation(usize),
+}
+
+/// A component marking the tint drawn over one end of the hinted move.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum HintHighlight {
+    From,
+    To,
+}
+
+fn spawn_hint_highlights(mut commands: Commands) {
+    for highlight in [HintHighlight::From, HintHighlight::To] {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: HINT_COLOR,
+                    ..default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(highlight);
+    }
+}
+
+fn clear_hint(mut hint: ResMut<Hint>) {
+    *hint = Hint::default();
+}
+
+/// Picks the move to point out when a hint is requested, and hides it once the game changes.
+/// Like the terminal version, a play to the foundation comes first, then a move between columns,
+/// then any other play.
+fn show_hint(
+    time: Res<Time>,
+    mut requests: EventReader<HintRequest>,
+    mut actions: EventReader<GameAction>,
+    game: Res<Game>,
+    mut hint: ResMut<Hint>,
+) {
+    if actions.iter().count() > 0 {
+        hint.ends = None;
+    }
+    if requests.iter().count() > 0 {
+        let play = game.hint_play();
+        let moved = game.hint().map(|legal_move| {
+            let (from, to) = grid_move(&game, legal_move);
+            (HintEnd::Grid(from), HintEnd::Grid(to))
+        });
+        hint.ends = match play {
+            Some(play) if play.to == Pile::Foundation => Some(play_ends(&game, play)),
+            _ => moved.or_else(|| play.map(|play| play_ends(&game, play))),
+        };
+        hint.timer = Timer::from_seconds(HINT_DURATION, false);
+        if hint.ends.is_none() {
+            bevy::log::trace!("no legal move to hint at");
+        }
+    }
+    if hint.timer.tick(time.delta()).just_finished() {
+        hint.ends = None;
+    }
+}
+
+/// Gets the ends of a play: the card played, and where it lands.
+fn play_ends(game: &Game, play: Play) -> (HintEnd, HintEnd) {
+    let played = match play.from {
+        Pile::Column(x) => game.columns()[x]
+            .last()
+            .map(|tableau_card| tableau_card.card),
+        Pile::Waste => game.waste().last().copied(),
+        Pile::Cell(cell) => game.free_cells()[cell],
+        Pile::Foundation => None,
+    };
+    let end = |pile, landing: bool| match pile {
+        Pile::Column(x) => {
+            let length = game.columns()[x].len();
+            let y = if landing {
+                length
+            } else {
+                length.saturating_sub(1)
+            };
+            HintEnd::Grid(GridPosition {
+                x: x as u8,
+                y: y as u8,
+            })
+        }
+        Pile::Waste => HintEnd::Waste,
+        Pile::Cell(cell) => HintEnd::Cell(cell),
+        Pile::Foundation => HintEnd::Foundation(played.map_or(0, |card| card.suit.index())),
+    };
+    (end(play.from, false), end(play.to, true))
+}
+
+fn draw_hint(
+    hint: Res<Hint>,
+    game: Res<Game>,
+    layout: Res<Layout>,
+    state: Res<State<AppState>>,
+    mut query: Query<(&HintHighlight, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let playing = *state.current() == AppState::Playing;
+    for (highlight, mut transform, mut sprite, mut visibility) in query.iter_mut() {
+        let end = hint.ends.map(|(from, to)| match highlight {
+            HintHighlight::From => from,
+            HintHighlight::To => to,
+        });
+        visibility.is_visible = playing && end.is_some();
+        if let Some(end) = end {
+            transform.translation = match end {
+                HintEnd::Grid(position) => layout
+                    .grid_center(&position)
+                    .extend(position.y as f32 + 0.5),
+                HintEnd::Waste => layout.waste_center().extend(0.5),
+                HintEnd::Cell(cell) => layout
+                    .cell_center(cell, game.free_cells().len())
+                    .extend(0.5),
+                HintEnd::Foundation(index) => layout.foundation_pile_center(index).extend(0.5),
+            };
+            sprite.custom_size = Some(Vec2::splat(layout.card_size + HINT_WIDTH * 2.0));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! The bar along the top of the window showing the score and progress of the game.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use spidertaire::game::Game;
+
+use super::layout::HUD_HEIGHT;
+use crate::{AppState, SPACING};
+
+const HUD_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+
+/// A plugin counting time while playing, and drawing it in a HUD bar along with the score and moves.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scoreboard>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Playing)
+                    .with_system(reset_scoreboard)
+                    .with_system(spawn_hud),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(despawn_hud))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(tick_clock))
+            .add_system(update_hud);
+    }
+}
+
+/// A resource counting the time spent playing the current game.
+#[derive(Debug, Default, Clone)]
+pub struct Scoreboard {
+    /// The time spent playing, excluding time spent paused.
+    pub elapsed: Duration,
+}
+
+/// A component marking the root node of the HUD.
+#[derive(Component)]
+struct Hud;
+
+/// A component marking the text of the HUD.
+#[derive(Component)]
+struct HudText;
+
+fn reset_scoreboard(mut scoreboard: ResMut<Scoreboard>) {
+    *scoreboard = Scoreboard::default();
+}
+
+fn tick_clock(time: Res<Time>, mut scoreboard: ResMut<Scoreboard>) {
+    scoreboard.elapsed += time.delta();
+}
+
+/// Spawns the HUD when a game starts, unless it is already shown from a previous game.
+fn spawn_hud(mut commands: Commands, assets: Res<AssetServer>, query: Query<(), With<Hud>>) {
+    if !query.is_empty() {
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(Hud)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(100.0), Val::Px(HUD_HEIGHT)),
+                        padding: UiRect {
+                            left: Val::Px(SPACING * 2.0),
+                            right: Val::Px(SPACING * 2.0),
+                            ..default()
+                        },
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    color: HUD_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: assets.load("fonts/arial.ttf"),
+                                font_size: HUD_HEIGHT * 0.6,
+                                color: Color::WHITE,
+                            },
+                        ))
+                        .insert(HudText);
+                });
+        });
+}
+
+fn despawn_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+/// Formats a duration as minutes and seconds.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn update_hud(
+    scoreboard: Res<Scoreboard>,
+    game: Res<Game>,
+    mut query: Query<&mut Text, With<HudText>>,
+) {
+    let value = format!(
+        "Score {}    Moves {}    Time {}    Deals left {}    Runs {}/{}",
+        game.score(),
+        game.moves(),
+        format_elapsed(scoreboard.elapsed),
+        game.stock().len(),
+        game.foundation().len(),
+        game.runs_to_win(),
+    );
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
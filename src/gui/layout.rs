@@ -0,0 +1,330 @@
+//! Positions and sizes of everything drawn on the table, derived from the window size.
+
+use bevy::prelude::*;
+
+use spidertaire::game::Game;
+use spidertaire::variant::Variant;
+
+use super::picking::Bounds2;
+use crate::{GridPosition, Hidden, Shown, SPACING, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+/// The vertical offset between overlapping grid cards, relative to the card size.
+const SPACING_Y_RATIO: f32 = 0.275;
+/// The vertical offset between overlapping grid cards in a portrait window, relative to the card size.
+/// Portrait windows have room to fan columns out further.
+const PORTRAIT_SPACING_Y_RATIO: f32 = 0.4;
+/// The gap between cards in a portrait window, relative to the gap in a landscape window.
+/// Narrow windows use a smaller gap so the cards can be larger.
+const PORTRAIT_GAP_RATIO: f32 = 0.4;
+/// The vertical offset between hidden grid cards, relative to the offset between shown cards.
+const HIDDEN_SPACING_RATIO: f32 = 0.5;
+/// The number of card sizes the grid must fit vertically, including the stock row.
+const MIN_ROWS: f32 = 4.0;
+/// The size in pixels of the card textures.
+const TEXTURE_SIZE: f32 = 200.0;
+/// The height of the HUD bar along the top of the window.
+pub const HUD_HEIGHT: f32 = 28.0;
+/// The size of the text drawn on shown cards, relative to the card size.
+const FONT_SIZE_RATIO: f32 = 0.22;
+/// The number of stock sets fanned out. Any further sets are stacked on the last, so a long stock
+/// such as Klondike's stays in its corner.
+const FANNED_SETS: usize = 5;
+/// The number of stock sets fanned out in variants with a waste, leaving room for the waste and
+/// the foundation piles in the top row.
+const WASTE_FANNED_SETS: usize = 3;
+
+/// A plugin keeping the [`Layout`] resource in sync with the primary window.
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        let columns = Variant::default().rules().layout().len();
+        app.insert_resource(Layout::new(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT), columns))
+            .add_system_to_stage(CoreStage::PreUpdate, update_layout)
+            .add_system_to_stage(CoreStage::PreUpdate, update_column_counts);
+    }
+}
+
+/// A resource describing where the stock and grid are drawn in world coordinates.
+/// World coordinates are measured in logical pixels with the origin at the centre of the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// The logical size of the window.
+    pub window_size: Vec2,
+    /// The number of grid columns, which depends on the variant being played.
+    pub columns: usize,
+    /// The width and height of a card.
+    pub card_size: f32,
+    /// The gap between neighbouring cards, and between the cards and the edges of the window.
+    pub gap: f32,
+    /// The vertical offset between overlapping grid cards in a column with room to spare.
+    pub spacing_y: f32,
+    /// The top-right corner of the stock.
+    pub available_corner: Vec2,
+    /// The top-left corner of the grid.
+    pub grid_corner: Vec2,
+    /// The number of hidden and shown cards in each grid column.
+    pub column_counts: Vec<(u8, u8)>,
+    /// The number of stock sets fanned out, which is fewer in variants with a waste.
+    pub fanned_sets: usize,
+}
+
+/// The vertical offsets used to fan out the cards of a single grid column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSpacing {
+    /// The number of hidden cards at the top of the column.
+    pub hidden_rows: u8,
+    /// The offset between a hidden card and the card below it.
+    pub hidden: f32,
+    /// The offset between a shown card and the card below it.
+    pub shown: f32,
+}
+
+impl ColumnSpacing {
+    /// Gets the distance from the top of the column to the top of the card in the given row.
+    pub fn offset(&self, row: u8) -> f32 {
+        let hidden_rows = row.min(self.hidden_rows);
+        let shown_rows = row - hidden_rows;
+        self.hidden * hidden_rows as f32 + self.shown * shown_rows as f32
+    }
+}
+
+impl Layout {
+    /// Computes the layout of the given number of grid columns for a window of the given logical size.
+    /// Portrait windows, such as phones held upright, use a tighter gap and fan columns out further.
+    pub fn new(window_size: Vec2, columns: usize) -> Self {
+        let portrait = window_size.y > window_size.x;
+        let (gap, spacing_y_ratio) = if portrait {
+            (SPACING * PORTRAIT_GAP_RATIO, PORTRAIT_SPACING_Y_RATIO)
+        } else {
+            (SPACING, SPACING_Y_RATIO)
+        };
+        let count = columns.max(1) as f32;
+        let card_size = ((window_size.x - gap * (count + 1.0)) / count)
+            .min((window_size.y - HUD_HEIGHT - gap * 3.0) / MIN_ROWS)
+            .max(1.0);
+        let margin_x = (window_size.x - card_size * count - gap * (count - 1.0)) / 2.0;
+        let top = window_size.y / 2.0 - HUD_HEIGHT - gap;
+        Layout {
+            window_size,
+            columns,
+            card_size,
+            gap,
+            spacing_y: card_size * spacing_y_ratio,
+            available_corner: Vec2::new(window_size.x / 2.0 - margin_x, top),
+            grid_corner: Vec2::new(window_size.x / -2.0 + margin_x, top - card_size - gap),
+            column_counts: Vec::new(),
+            fanned_sets: FANNED_SETS,
+        }
+    }
+
+    /// Computes the spacing for a column with the given number of hidden and shown cards.
+    /// Hidden cards are always packed more tightly than shown cards. When the column would
+    /// run past the bottom of the window, the shown cards are squeezed until only their labels
+    /// remain visible, after which the whole column is compressed to fit.
+    pub fn column_spacing(&self, hidden_rows: u8, shown_rows: u8) -> ColumnSpacing {
+        let mut hidden = self.spacing_y * HIDDEN_SPACING_RATIO;
+        let mut shown = self.spacing_y;
+        let hidden_gaps = if shown_rows == 0 {
+            hidden_rows.saturating_sub(1) as f32
+        } else {
+            hidden_rows as f32
+        };
+        let shown_gaps = shown_rows.saturating_sub(1) as f32;
+        let available =
+            (self.grid_corner.y - self.card_size + self.window_size.y / 2.0 - self.gap).max(0.0);
+
+        let overflow = hidden * hidden_gaps + shown * shown_gaps - available;
+        if overflow > 0.0 && shown_gaps > 0.0 {
+            shown = (shown - overflow / shown_gaps).max(self.font_size().min(shown));
+        }
+        let total = hidden * hidden_gaps + shown * shown_gaps;
+        if total > available {
+            let scale = available / total;
+            hidden *= scale;
+            shown *= scale;
+        }
+        ColumnSpacing {
+            hidden_rows,
+            hidden,
+            shown,
+        }
+    }
+
+    /// Gets the scale to apply to card textures so they are drawn at the card size.
+    pub fn texture_scale(&self) -> f32 {
+        self.card_size / TEXTURE_SIZE
+    }
+
+    /// Gets the size of the text drawn on shown cards.
+    pub fn font_size(&self) -> f32 {
+        self.card_size * FONT_SIZE_RATIO
+    }
+
+    /// Gets the spacing of the given grid column.
+    pub fn grid_spacing(&self, column: u8) -> ColumnSpacing {
+        let (hidden_rows, shown_rows) = self
+            .column_counts
+            .get(column as usize)
+            .copied()
+            .unwrap_or_default();
+        self.column_spacing(hidden_rows, shown_rows)
+    }
+
+    /// Gets the centre of the card at the given grid position.
+    pub fn grid_center(&self, position: &GridPosition) -> Vec2 {
+        Vec2::new(
+            self.grid_corner.x
+                + (self.card_size + self.gap) * position.x as f32
+                + self.card_size / 2.0,
+            self.grid_corner.y
+                - self.grid_spacing(position.x).offset(position.y)
+                - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the centre of the stock set drawn at the given index, counting from the right.
+    pub fn available_center(&self, index: usize) -> Vec2 {
+        let index = index.min(self.fanned_sets - 1);
+        Vec2::new(
+            self.available_corner.x - (self.card_size / 2.0) * index as f32 - self.card_size / 2.0,
+            self.available_corner.y - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the centre of the top card of the waste, to the left of the fanned out stock.
+    pub fn waste_center(&self) -> Vec2 {
+        let fanned = self.available_bounds(self.fanned_sets).size.x;
+        Vec2::new(
+            self.available_corner.x - fanned - self.gap - self.card_size / 2.0,
+            self.available_corner.y - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the centre of the free cell at the given index out of `count`, in a row ending at the top
+    /// right corner where the stock would be.
+    pub fn cell_center(&self, index: usize, count: usize) -> Vec2 {
+        let from_right = count.saturating_sub(index + 1) as f32;
+        Vec2::new(
+            self.available_corner.x
+                - self.card_size / 2.0
+                - (self.card_size + self.gap) * from_right,
+            self.available_corner.y - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the centre of the completed run drawn at the given index, counting from the left.
+    pub fn foundation_center(&self, index: usize) -> Vec2 {
+        Vec2::new(
+            self.grid_corner.x + (self.card_size / 2.0) * index as f32 + self.card_size / 2.0,
+            self.available_corner.y - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the centre of the foundation pile at the given index, counting from the left. Unlike
+    /// completed runs, foundation piles do not overlap, so the suit of each one can be seen.
+    pub fn foundation_pile_center(&self, index: usize) -> Vec2 {
+        Vec2::new(
+            self.grid_corner.x + (self.card_size + self.gap) * index as f32 + self.card_size / 2.0,
+            self.available_corner.y - self.card_size / 2.0,
+        )
+    }
+
+    /// Gets the area covered by the given number of stock sets.
+    pub fn available_bounds(&self, count: usize) -> Bounds2 {
+        let count = count.min(self.fanned_sets);
+        let width = self.card_size / 2.0 * count.saturating_sub(1) as f32 + self.card_size;
+        Bounds2 {
+            pos: Vec2::new(
+                self.available_corner.x - width,
+                self.available_corner.y - self.card_size,
+            ),
+            size: Vec2::new(width, self.card_size),
+        }
+    }
+
+    /// Gets the area covered by a card centred on the given point.
+    pub fn card_bounds(&self, center: Vec2) -> Bounds2 {
+        Bounds2 {
+            pos: center - Vec2::splat(self.card_size / 2.0),
+            size: Vec2::splat(self.card_size),
+        }
+    }
+
+    /// Gets the grid column spanning the given horizontal world coordinate, including half the gap on either side.
+    pub fn grid_column(&self, world_x: f32) -> Option<u8> {
+        let column =
+            ((world_x - self.grid_corner.x + self.gap / 2.0) / (self.card_size + self.gap)).floor();
+        (0.0..self.columns as f32)
+            .contains(&column)
+            .then_some(column as u8)
+    }
+
+    /// Converts a cursor position, measured from the bottom-left of the window, to world coordinates.
+    pub fn world_position(&self, cursor_position: Vec2) -> Vec2 {
+        cursor_position - self.window_size / 2.0
+    }
+
+    /// Converts a touch position, measured from the top-left of the window, to world coordinates.
+    pub fn touch_world_position(&self, touch_position: Vec2) -> Vec2 {
+        Vec2::new(
+            touch_position.x - self.window_size.x / 2.0,
+            self.window_size.y / 2.0 - touch_position.y,
+        )
+    }
+}
+
+/// Recomputes the layout whenever the logical size of the primary window differs from it,
+/// whether the window was resized, moved to a display with a different scale factor, or
+/// created at a size other than the default.
+fn update_layout(mut layout: ResMut<Layout>, windows: Res<Windows>) {
+    if let Some(window) = windows.get_primary() {
+        let window_size = Vec2::new(window.width(), window.height());
+        if layout.window_size == window_size {
+            return;
+        }
+        bevy::log::debug!("updating layout for window size {:?}", window_size);
+        let mut new_layout = Layout::new(window_size, layout.columns);
+        new_layout.column_counts = layout.column_counts.clone();
+        new_layout.fanned_sets = layout.fanned_sets;
+        *layout = new_layout;
+    }
+}
+
+/// Counts the hidden and shown cards in each grid column, recomputing the layout first when the
+/// variant being played has a different number of columns.
+fn update_column_counts(
+    mut layout: ResMut<Layout>,
+    game: Res<Game>,
+    hidden: Query<&GridPosition, With<Hidden>>,
+    shown: Query<&GridPosition, With<Shown>>,
+) {
+    let columns = game.columns().len();
+    if layout.columns != columns {
+        bevy::log::debug!("updating layout for {} columns", columns);
+        *layout = Layout::new(layout.window_size, columns);
+    }
+    let fanned_sets = if game.rules().has_waste() {
+        WASTE_FANNED_SETS
+    } else {
+        FANNED_SETS
+    };
+    if layout.fanned_sets != fanned_sets {
+        layout.fanned_sets = fanned_sets;
+    }
+    let mut column_counts = vec![(0, 0); columns];
+    // cards of a previous game with more columns may remain until the end of the frame
+    for position in hidden.iter() {
+        if let Some(counts) = column_counts.get_mut(position.x as usize) {
+            counts.0 += 1;
+        }
+    }
+    for position in shown.iter() {
+        if let Some(counts) = column_counts.get_mut(position.x as usize) {
+            counts.1 += 1;
+        }
+    }
+    if layout.column_counts != column_counts {
+        layout.column_counts = column_counts;
+    }
+}
@@ -0,0 +1,16 @@
+//! Plugins and systems making up the user interface around the card grid.
+
+pub mod animation;
+pub mod assist;
+pub mod gamepad;
+pub mod hint;
+pub mod hud;
+pub mod layout;
+pub mod picking;
+pub mod piles;
+pub mod replay;
+pub mod screens;
+pub mod selection;
+pub mod settings;
+pub mod stats;
+pub mod touch;
@@ -0,0 +1,100 @@
+//! Hit testing of overlapping items drawn on the table.
+
+use bevy::prelude::*;
+
+/// An axis-aligned rectangle in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds2 {
+    /// The bottom-left corner of the rectangle.
+    pub pos: Vec2,
+    /// The width and height of the rectangle.
+    pub size: Vec2,
+}
+
+impl Bounds2 {
+    /// Determines whether the given point lies within the rectangle, including its edges.
+    pub fn contains(&self, coords: Vec2) -> bool {
+        coords.x >= self.pos.x
+            && coords.y >= self.pos.y
+            && coords.x <= self.pos.x + self.size.x
+            && coords.y <= self.pos.y + self.size.y
+    }
+}
+
+/// An item that can be picked, along with where and how deep it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pickable<T> {
+    /// The value returned when this item is picked.
+    pub item: T,
+    /// The area covered by the item.
+    pub bounds: Bounds2,
+    /// The z coordinate of the item. Items with a greater depth are drawn on top.
+    pub depth: f32,
+}
+
+/// Picks the item drawn on top at the given point.
+/// Only the uncovered part of an item can pick it, since any item overlapping it is drawn above.
+pub fn pick<T, I>(pickables: I, point: Vec2) -> Option<T>
+where
+    I: IntoIterator<Item = Pickable<T>>,
+{
+    pickables
+        .into_iter()
+        .filter(|pickable| pickable.bounds.contains(point))
+        .max_by(|a, b| a.depth.total_cmp(&b.depth))
+        .map(|pickable| pickable.item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD_SIZE: f32 = 100.0;
+    const OVERLAP: f32 = 25.0;
+
+    /// Creates a column of cards fanned downwards from the origin, one per row.
+    fn column(rows: u8) -> Vec<Pickable<u8>> {
+        (0..rows)
+            .map(|row| Pickable {
+                item: row,
+                bounds: Bounds2 {
+                    pos: Vec2::new(0.0, -(row as f32) * OVERLAP - CARD_SIZE),
+                    size: Vec2::splat(CARD_SIZE),
+                },
+                depth: row as f32,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn picks_visible_strip_of_covered_card() {
+        let cards = column(3);
+        assert_eq!(pick(cards.clone(), Vec2::new(50.0, -10.0)), Some(0));
+        assert_eq!(pick(cards, Vec2::new(50.0, -40.0)), Some(1));
+    }
+
+    #[test]
+    fn picks_topmost_card_where_cards_overlap() {
+        let cards = column(3);
+        assert_eq!(pick(cards.clone(), Vec2::new(50.0, -90.0)), Some(2));
+        assert_eq!(pick(cards, Vec2::new(50.0, -140.0)), Some(2));
+    }
+
+    #[test]
+    fn picks_topmost_card_regardless_of_order() {
+        let mut cards = column(4);
+        cards.reverse();
+        assert_eq!(pick(cards.clone(), Vec2::new(50.0, -90.0)), Some(3));
+        cards.swap(0, 2);
+        assert_eq!(pick(cards, Vec2::new(50.0, -60.0)), Some(2));
+    }
+
+    #[test]
+    fn picks_nothing_outside_cards() {
+        let cards = column(3);
+        assert_eq!(pick(cards.clone(), Vec2::new(150.0, -50.0)), None);
+        assert_eq!(pick(cards.clone(), Vec2::new(50.0, 10.0)), None);
+        assert_eq!(pick(cards, Vec2::new(50.0, -160.0)), None);
+        assert_eq!(pick(Vec::<Pickable<u8>>::new(), Vec2::ZERO), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! The waste, the free cells and the foundation piles of variants such as Klondike and FreeCell.
+//!
+//! Only the top card of each pile is drawn, with a faint outline for each empty free cell and
+//! foundation pile. They are all respawned whenever the game or the layout changes, as cards only
+//! reach or leave them one at a time.
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Pile};
+use spidertaire::{Card, CardSuit};
+
+use super::layout::Layout;
+use super::touch::LastTouch;
+use crate::{find_pile_play, spawn_card_labels, AppState, GameAction, PlayingSystem};
+
+/// A plugin drawing the top cards of the waste, free cells and foundation piles, and playing the
+/// waste or a free cell when it is clicked.
+pub struct PilesPlugin;
+
+impl Plugin for PilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Menu).with_system(despawn_piles))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing).with_system(
+                    handle_pile_input
+                        .label(PlayingSystem::Input)
+                        .after(PlayingSystem::GameOver),
+                ),
+            )
+            .add_system(draw_piles.after(PlayingSystem::Collect));
+    }
+}
+
+/// A component marking the top card of the waste, a free cell or a foundation pile.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum PileCard {
+    /// The top card of the waste.
+    Waste,
+    /// The free cell at the given index in [`Game::free_cells`], which may be empty.
+    Cell(usize),
+    /// The foundation pile at the given index in [`Game::foundation_piles`].
+    Foundation(usize),
+}
+
+impl PileCard {
+    /// Gets the position of the card.
+    fn translation(&self, game: &Game, layout: &Layout) -> Vec3 {
+        match self {
+            PileCard::Waste => layout.waste_center().extend(0.0),
+            PileCard::Cell(index) => layout
+                .cell_center(*index, game.free_cells().len())
+                .extend(0.0),
+            PileCard::Foundation(index) => layout.foundation_pile_center(*index).extend(0.0),
+        }
+    }
+}
+
+fn despawn_piles(mut commands: Commands, query: Query<Entity, With<PileCard>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+/// Replaces the top cards of the piles whenever the game or the layout changes.
+fn draw_piles(
+    mut commands: Commands,
+    game: Res<Game>,
+    layout: Res<Layout>,
+    state: Res<State<AppState>>,
+    assets: Res<AssetServer>,
+    query: Query<Entity, With<PileCard>>,
+) {
+    if (!game.is_changed() && !layout.is_changed()) || *state.current() == AppState::Menu {
+        return;
+    }
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    let foundation_piles = game
+        .foundation_piles()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, pile)| Some((PileCard::Foundation(index), *pile.last()?)));
+    let cells = game
+        .free_cells()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, cell)| Some((PileCard::Cell(index), (*cell)?)));
+    let waste = game.waste().last().map(|card| (PileCard::Waste, *card));
+    for (pile, card) in waste.into_iter().chain(cells).chain(foundation_piles) {
+        spawn_pile_card(&mut commands, &game, &layout, &assets, pile, card);
+    }
+    let empty_cells = game
+        .free_cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.is_none())
+        .map(|(index, _)| PileCard::Cell(index));
+    // a completed suit's king is drawn in place of its empty pile
+    let empty_foundation_piles = game
+        .foundation_piles()
+        .iter()
+        .enumerate()
+        .filter(|(index, pile)| {
+            pile.is_empty()
+                && CardSuit::from_index(*index)
+                    .map_or(false, |suit| !game.foundation().contains(&suit))
+        })
+        .map(|(index, _)| PileCard::Foundation(index));
+    for pile in empty_cells.chain(empty_foundation_piles) {
+        spawn_outline(&mut commands, &game, &layout, pile);
+    }
+}
+
+/// Spawns the outline of an empty free cell or foundation pile.
+fn spawn_outline(commands: &mut Commands, game: &Game, layout: &Layout, pile: PileCard) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.15),
+                custom_size: Some(Vec2::splat(layout.card_size)),
+                ..default()
+            },
+            transform: Transform::from_translation(pile.translation(game, layout)),
+            ..default()
+        })
+        .insert(pile);
+}
+
+/// Spawns the face up top card of a pile.
+fn spawn_pile_card(
+    commands: &mut Commands,
+    game: &Game,
+    layout: &Layout,
+    assets: &AssetServer,
+    pile: PileCard,
+    card: Card,
+) {
+    let translation = pile.translation(game, layout);
+    let e = commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::splat(layout.card_size)),
+                ..default()
+            },
+            transform: Transform::from_translation(translation),
+            ..default()
+        })
+        .insert(pile)
+        .id();
+    spawn_card_labels(commands, e, &card, layout, assets);
+}
+
+/// Finds the waste or occupied free cell under the given world position.
+pub fn pile_at(game: &Game, layout: &Layout, position: Vec2) -> Option<Pile> {
+    let count = game.free_cells().len();
+    let cells = game
+        .free_cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.is_some())
+        .map(|(index, _)| (Pile::Cell(index), layout.cell_center(index, count)));
+    let waste = (!game.waste().is_empty()).then(|| (Pile::Waste, layout.waste_center()));
+    waste
+        .into_iter()
+        .chain(cells)
+        .find(|(_, center)| layout.card_bounds(*center).contains(position))
+        .map(|(pile, _)| pile)
+}
+
+/// Plays the top card of the waste or the card in a free cell when it is clicked, to the
+/// foundation when it can go there.
+fn handle_pile_input(
+    mouse_input: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    last_touch: Res<LastTouch>,
+    windows: Res<Windows>,
+    layout: Res<Layout>,
+    game: Res<Game>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) || last_touch.suppresses_mouse(&time) {
+        return;
+    }
+    let clicked = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+        .map(|position| layout.world_position(position))
+        .and_then(|position| pile_at(&game, &layout, position));
+    if let Some(pile) = clicked {
+        match find_pile_play(&game, pile) {
+            Some(play) => actions.send(GameAction::Play(play)),
+            None => bevy::log::trace!("no legal play from {}", pile),
+        }
+    }
+}
@@ -0,0 +1,463 @@
+//! Recording every game as a replay, and a viewer stepping through recorded games.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use spidertaire::game::Game;
+use spidertaire::replay::Replay;
+
+use super::layout::HUD_HEIGHT;
+use crate::{
+    respawn_game, AppState, Available, Foundation, GameAction, GameEntities, GridPosition,
+    LegalMoves, PlayingSystem, SPACING,
+};
+
+/// The time between actions when playing back at normal speed, in seconds.
+const STEP_INTERVAL: f32 = 0.6;
+/// The playback speeds the viewer cycles through, as multiples of the normal speed.
+const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 4.0, 8.0];
+/// The speed the viewer starts at, as an index into [`SPEEDS`].
+const DEFAULT_SPEED: usize = 1;
+const BAR_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const BUTTON_WIDTH: f32 = 80.0;
+/// The keys controlling the viewer, along with the button each does the same as.
+const VIEWER_KEYS: [(KeyCode, ViewerButton); 6] = [
+    (KeyCode::Left, ViewerButton::Back),
+    (KeyCode::Space, ViewerButton::PlayPause),
+    (KeyCode::Right, ViewerButton::Forward),
+    (KeyCode::Down, ViewerButton::Slower),
+    (KeyCode::Up, ViewerButton::Faster),
+    (KeyCode::Escape, ViewerButton::Exit),
+];
+
+/// A plugin saving a replay of every game as it is played, and playing replays back in [`AppState::Replay`].
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplaySource>()
+            .init_resource::<Recording>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(start_recording))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(record_replay.after(PlayingSystem::Action)),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Replay).with_system(start_viewer))
+            .add_system_set(
+                SystemSet::on_update(AppState::Replay)
+                    .with_system(control_viewer.before(PlayingSystem::Action))
+                    .with_system(update_viewer_bar.after(control_viewer))
+                    .with_system(highlight_viewer_buttons),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Replay).with_system(stop_viewer));
+    }
+}
+
+/// A resource holding the replay file to watch next, such as one given on the command line.
+/// The most recently recorded game is watched when there is none.
+#[derive(Debug, Default)]
+pub struct ReplaySource(pub Option<PathBuf>);
+
+/// A resource holding the file the replay of the current game is saved to, once it has been chosen.
+#[derive(Debug, Default)]
+struct Recording {
+    path: Option<PathBuf>,
+}
+
+/// A resource holding the replay being watched, and how far through it the viewer is.
+#[derive(Debug)]
+struct ReplayViewer {
+    replay: Replay,
+    /// The number of actions played so far.
+    step: usize,
+    playing: bool,
+    /// The playback speed, as an index into [`SPEEDS`].
+    speed: usize,
+    /// The time until the next action is played.
+    timer: Timer,
+}
+
+impl ReplayViewer {
+    fn new(replay: Replay) -> Self {
+        ReplayViewer {
+            replay,
+            step: 0,
+            playing: false,
+            speed: DEFAULT_SPEED,
+            timer: step_timer(DEFAULT_SPEED),
+        }
+    }
+}
+
+/// Creates the timer between actions played back at the given speed.
+fn step_timer(speed: usize) -> Timer {
+    Timer::from_seconds(STEP_INTERVAL / SPEEDS[speed], true)
+}
+
+/// A component representing a control of the viewer.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewerButton {
+    Back,
+    PlayPause,
+    Forward,
+    Slower,
+    Faster,
+    Exit,
+}
+
+impl ViewerButton {
+    /// Creates an array of all the viewer's buttons, in the order they are shown.
+    fn all() -> [ViewerButton; 6] {
+        [
+            ViewerButton::Back,
+            ViewerButton::PlayPause,
+            ViewerButton::Forward,
+            ViewerButton::Slower,
+            ViewerButton::Faster,
+            ViewerButton::Exit,
+        ]
+    }
+
+    /// Returns the label shown on the button, which may depend on whether the replay is playing.
+    fn label(&self, viewer: &ReplayViewer) -> &'static str {
+        match self {
+            ViewerButton::Back => "Back",
+            ViewerButton::PlayPause if viewer.playing => "Pause",
+            ViewerButton::PlayPause => "Play",
+            ViewerButton::Forward => "Forward",
+            ViewerButton::Slower => "Slower",
+            ViewerButton::Faster => "Faster",
+            ViewerButton::Exit => "Exit",
+        }
+    }
+}
+
+/// A component marking the root node of the viewer's controls.
+#[derive(Component)]
+struct ViewerBar;
+
+/// A component marking the text describing the replay and the viewer's progress through it.
+#[derive(Component)]
+struct ViewerText;
+
+fn start_recording(mut recording: ResMut<Recording>) {
+    recording.path = None;
+}
+
+/// Saves the replay of the current game after every action, so it is kept however the game ends.
+fn record_replay(game: Res<Game>, mut recording: ResMut<Recording>) {
+    if !game.is_changed() || game.actions().is_empty() {
+        return;
+    }
+    if recording.path.is_none() {
+        // the clock is only read where replays can be saved, as it panics on the web
+        recording.path = Replay::default_dir().map(|dir| {
+            let started = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            dir.join(Replay::file_name(started, game.seed()))
+        });
+    }
+    if let Some(path) = &recording.path {
+        if let Err(e) = Replay::from(&*game).save(path) {
+            bevy::log::warn!("unable to save replay to {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Loads the replay to watch, falling back to the most recently recorded game.
+fn load_replay(source: &mut ReplaySource) -> Result<Replay, String> {
+    let path = match source.0.take() {
+        Some(path) => path,
+        None => {
+            let dir = Replay::default_dir()
+                .ok_or_else(|| String::from("there is no directory replays are saved to"))?;
+            Replay::latest(&dir)
+                .map_err(|e| format!("unable to read {:?}: {}", dir, e))?
+                .ok_or_else(|| String::from("no games have been recorded"))?
+        }
+    };
+    Replay::load(&path).map_err(|e| format!("unable to load replay from {:?}: {}", path, e))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_viewer(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut state: ResMut<State<AppState>>,
+    mut source: ResMut<ReplaySource>,
+    mut game: ResMut<Game>,
+    game_entities: GameEntities,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut available_sets: ResMut<Vec<Available>>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut foundation: ResMut<Foundation>,
+) {
+    let replay = match load_replay(&mut source) {
+        Ok(replay) => replay,
+        Err(e) => {
+            bevy::log::warn!("unable to watch replay: {}", e);
+            if let Err(e) = state.replace(AppState::Menu) {
+                bevy::log::warn!("unable to leave replay: {:?}", e);
+            }
+            return;
+        }
+    };
+    bevy::log::info!(
+        "watching {} {} game with seed {} and {} actions",
+        replay.difficulty,
+        replay.variant.as_str(),
+        replay.seed,
+        replay.actions.len()
+    );
+    *game = Game::with_variant(replay.variant, replay.difficulty, replay.seed);
+    respawn_game(
+        &mut commands,
+        &game,
+        &game_entities,
+        &mut grid_cards,
+        &mut available_sets,
+        &mut legal_moves,
+        &mut foundation,
+    );
+    let viewer = ReplayViewer::new(replay);
+    spawn_viewer_bar(&mut commands, &assets, &viewer);
+    commands.insert_resource(viewer);
+}
+
+fn spawn_viewer_bar(commands: &mut Commands, assets: &AssetServer, viewer: &ReplayViewer) {
+    let font = assets.load("fonts/arial.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Px(HUD_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(0.0),
+                    ..default()
+                },
+                padding: UiRect {
+                    left: Val::Px(SPACING * 2.0),
+                    right: Val::Px(SPACING * 2.0),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: BAR_COLOR.into(),
+            ..default()
+        })
+        .insert(ViewerBar)
+        .with_children(|parent| {
+            for button in ViewerButton::all() {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(BUTTON_WIDTH), Val::Px(HUD_HEIGHT - SPACING)),
+                            margin: UiRect {
+                                right: Val::Px(SPACING),
+                                ..default()
+                            },
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: BUTTON_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle::from_section(
+                            button.label(viewer),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: HUD_HEIGHT * 0.55,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+            parent
+                .spawn_bundle(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: HUD_HEIGHT * 0.6,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect {
+                            left: Val::Px(SPACING * 2.0),
+                            ..default()
+                        },
+                        ..default()
+                    }),
+                )
+                .insert(ViewerText);
+        });
+}
+
+fn stop_viewer(mut commands: Commands, query: Query<Entity, With<ViewerBar>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    commands.remove_resource::<ReplayViewer>();
+}
+
+/// Steps through the replay in response to the viewer's buttons and keys, and while it is playing.
+/// Stepping forward performs the next action on the game like a player would, so it is animated;
+/// stepping back replays the game from the start to the previous action.
+#[allow(clippy::too_many_arguments)]
+fn control_viewer(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<State<AppState>>,
+    viewer: Option<ResMut<ReplayViewer>>,
+    mut actions: EventWriter<GameAction>,
+    mut game: ResMut<Game>,
+    game_entities: GameEntities,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut available_sets: ResMut<Vec<Available>>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut foundation: ResMut<Foundation>,
+    buttons: Query<(&Interaction, &ViewerButton), Changed<Interaction>>,
+) {
+    let mut viewer = match viewer {
+        Some(viewer) => viewer,
+        None => return,
+    };
+    let mut pressed = buttons
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, button)| *button)
+        .collect::<Vec<ViewerButton>>();
+    for (key, button) in VIEWER_KEYS {
+        if keyboard_input.just_pressed(key) {
+            pressed.push(button);
+        }
+    }
+    let length = viewer.replay.actions.len();
+    let mut step_forward = false;
+    let mut back_to = None;
+    for button in pressed {
+        match button {
+            ViewerButton::Back => {
+                viewer.playing = false;
+                back_to = viewer.step.checked_sub(1).or(back_to);
+            }
+            ViewerButton::PlayPause => {
+                viewer.playing = !viewer.playing;
+                // playing from the end starts again from the beginning
+                if viewer.playing && viewer.step == length {
+                    back_to = Some(0);
+                }
+                viewer.timer.reset();
+            }
+            ViewerButton::Forward => {
+                viewer.playing = false;
+                step_forward = true;
+            }
+            ViewerButton::Slower => {
+                viewer.speed = viewer.speed.saturating_sub(1);
+                viewer.timer = step_timer(viewer.speed);
+            }
+            ViewerButton::Faster => {
+                viewer.speed = (viewer.speed + 1).min(SPEEDS.len() - 1);
+                viewer.timer = step_timer(viewer.speed);
+            }
+            ViewerButton::Exit => {
+                if let Err(e) = state.replace(AppState::Menu) {
+                    bevy::log::warn!("unable to leave replay: {:?}", e);
+                }
+            }
+        }
+    }
+    if viewer.playing && viewer.timer.tick(time.delta()).just_finished() {
+        step_forward = true;
+    }
+    if let Some(step) = back_to {
+        match viewer.replay.game_at(step) {
+            Ok(replayed) => {
+                *game = replayed;
+                viewer.step = step;
+                respawn_game(
+                    &mut commands,
+                    &game,
+                    &game_entities,
+                    &mut grid_cards,
+                    &mut available_sets,
+                    &mut legal_moves,
+                    &mut foundation,
+                );
+            }
+            Err(e) => bevy::log::warn!("unable to step back through replay: {}", e),
+        }
+    } else if step_forward && viewer.step < length {
+        // at most one action is sent each frame, as the cards of the last one are spawned at the end of the frame
+        let action = viewer.replay.actions[viewer.step];
+        actions.send(GameAction::from_action(action, &game));
+        viewer.step += 1;
+    }
+    if viewer.step == length {
+        viewer.playing = false;
+    }
+}
+
+fn update_viewer_bar(
+    viewer: Option<Res<ReplayViewer>>,
+    game: Res<Game>,
+    buttons: Query<(&ViewerButton, &Children)>,
+    mut texts: Query<&mut Text, Without<ViewerText>>,
+    mut viewer_texts: Query<&mut Text, With<ViewerText>>,
+) {
+    let viewer = match viewer {
+        Some(viewer) if viewer.is_changed() || game.is_changed() => viewer,
+        _ => return,
+    };
+    let value = format!(
+        "{} ({}), seed {}    Step {}/{}    Speed {}x    Score {}    Runs {}/{}",
+        viewer.replay.variant.as_str(),
+        viewer.replay.difficulty,
+        viewer.replay.seed,
+        viewer.step,
+        viewer.replay.actions.len(),
+        SPEEDS[viewer.speed],
+        game.score(),
+        game.foundation().len(),
+        game.runs_to_win(),
+    );
+    for mut text in viewer_texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                let label = button.label(&viewer);
+                if text.sections[0].value != label {
+                    text.sections[0].value = String::from(label);
+                }
+            }
+        }
+    }
+}
+
+fn highlight_viewer_buttons(
+    mut query: Query<(&Interaction, &mut UiColor), (Changed<Interaction>, With<ViewerButton>)>,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::Hovered | Interaction::Clicked => BUTTON_HOVERED_COLOR.into(),
+            Interaction::None => BUTTON_COLOR.into(),
+        };
+    }
+}
@@ -0,0 +1,454 @@
+//! The menu, pause, game over, statistics and settings screens, and the buttons moving between them.
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Status};
+use spidertaire::settings::{CardBack, Settings};
+use spidertaire::stats::Statistics;
+use spidertaire::variant::Variant;
+use spidertaire::Suits;
+
+use super::stats::{save_statistics, statistics_text};
+use crate::{AppState, Difficulty, GameSeed, SPACING};
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.3, 0.1);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+/// The animation speeds the settings screen cycles through.
+const ANIMATION_SPEEDS: [f32; 4] = [0.5, 1.0, 1.5, 2.0];
+/// The window sizes the settings screen cycles through.
+const WINDOW_SIZES: [(f32, f32); 4] = [
+    (960.0, 540.0),
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+];
+
+/// A plugin spawning and despawning the screen belonging to each non-playing state.
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Menu).with_system(spawn_menu))
+            .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(despawn_screen))
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause))
+            .add_system_set(SystemSet::on_exit(AppState::Paused).with_system(despawn_screen))
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(spawn_game_over))
+            .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(despawn_screen))
+            .add_system_set(SystemSet::on_enter(AppState::Statistics).with_system(spawn_statistics))
+            .add_system_set(SystemSet::on_exit(AppState::Statistics).with_system(despawn_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::Statistics).with_system(update_statistics),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Settings).with_system(spawn_settings))
+            .add_system_set(SystemSet::on_exit(AppState::Settings).with_system(despawn_screen))
+            .add_system(handle_screen_buttons)
+            .add_system(label_screen_buttons)
+            .add_system(highlight_screen_buttons);
+    }
+}
+
+/// A component marking the root node of a screen.
+#[derive(Component)]
+struct Screen;
+
+/// A component marking the text below a screen's title.
+#[derive(Component)]
+struct ScreenBody;
+
+/// A component representing the action taken when a screen button is clicked.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum ScreenButton {
+    Start(Difficulty),
+    CycleVariant,
+    Resume,
+    NewGame,
+    MainMenu,
+    ToggleAssist,
+    WatchReplay,
+    Statistics,
+    ResetStatistics,
+    Settings,
+    CycleDifficulty,
+    CycleCustomSuits,
+    ToggleColourRule,
+    ToggleAnimations,
+    CycleAnimationSpeed,
+    CycleCardBack,
+    CycleWindowSize,
+}
+
+/// Gets the item after the current one, wrapping around, or the first item if the current one is not listed.
+fn cycle<T: Copy + PartialEq>(items: &[T], current: T) -> T {
+    let next = items
+        .iter()
+        .position(|item| *item == current)
+        .map_or(0, |index| (index + 1) % items.len());
+    items[next]
+}
+
+/// Formats a setting that is either on or off.
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+impl ScreenButton {
+    /// Returns the label shown on the button, which may depend on the current settings.
+    pub fn label(&self, settings: &Settings) -> String {
+        match self {
+            ScreenButton::Start(difficulty) => difficulty.to_string(),
+            ScreenButton::CycleVariant => format!("Variant: {}", settings.variant.as_str()),
+            ScreenButton::Resume => String::from("Resume"),
+            ScreenButton::NewGame => String::from("New game"),
+            ScreenButton::MainMenu => String::from("Main menu"),
+            ScreenButton::ToggleAssist => format!("Assist: {}", on_off(settings.assist)),
+            ScreenButton::WatchReplay => String::from("Watch last game"),
+            ScreenButton::Statistics => String::from("Statistics"),
+            ScreenButton::ResetStatistics => String::from("Reset"),
+            ScreenButton::Settings => String::from("Settings"),
+            ScreenButton::CycleDifficulty => {
+                format!("Difficulty: {}", settings.difficulty)
+            }
+            ScreenButton::CycleCustomSuits => {
+                format!("Custom suits: {}", settings.custom_difficulty.suits())
+            }
+            ScreenButton::ToggleColourRule => format!(
+                "Custom runs by colour: {}",
+                on_off(settings.custom_difficulty.groups_by_colour())
+            ),
+            ScreenButton::ToggleAnimations => {
+                format!("Animations: {}", on_off(settings.animations))
+            }
+            ScreenButton::CycleAnimationSpeed => format!("Speed: {}x", settings.animation_speed),
+            ScreenButton::CycleCardBack => format!("Card back: {}", settings.card_back.as_str()),
+            ScreenButton::CycleWindowSize => format!(
+                "Window: {}x{}",
+                settings.window_width.round(),
+                settings.window_height.round()
+            ),
+        }
+    }
+}
+
+fn spawn_screen(
+    commands: &mut Commands,
+    assets: &AssetServer,
+    title: &str,
+    body: &str,
+    buttons: &[ScreenButton],
+    background: Color,
+    settings: &Settings,
+) {
+    let font = assets.load("fonts/arial.ttf");
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: background.into(),
+            ..default()
+        })
+        .insert(Screen)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 48.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(SPACING * 4.0)),
+                    ..default()
+                }),
+            );
+            if !body.is_empty() {
+                parent
+                    .spawn_bundle(
+                        TextBundle::from_section(
+                            body,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(SPACING * 2.0)),
+                            ..default()
+                        }),
+                    )
+                    .insert(ScreenBody);
+            }
+            for button in buttons {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(260.0), Val::Px(50.0)),
+                            margin: UiRect::all(Val::Px(SPACING)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: BUTTON_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(*button)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle::from_section(
+                            button.label(settings),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn spawn_menu(mut commands: Commands, assets: Res<AssetServer>, settings: Res<Settings>) {
+    let mut buttons = vec![ScreenButton::CycleVariant];
+    buttons.extend(Difficulty::all().map(ScreenButton::Start));
+    buttons.push(ScreenButton::Start(settings.custom_difficulty));
+    buttons.push(ScreenButton::WatchReplay);
+    buttons.push(ScreenButton::Statistics);
+    buttons.push(ScreenButton::Settings);
+    spawn_screen(
+        &mut commands,
+        &assets,
+        "Spidertaire",
+        "",
+        &buttons,
+        BACKGROUND_COLOR,
+        &settings,
+    );
+}
+
+fn spawn_pause(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    settings: Res<Settings>,
+    seed: Res<GameSeed>,
+) {
+    spawn_screen(
+        &mut commands,
+        &assets,
+        "Paused",
+        &format!("Seed {}", seed.current),
+        &[
+            ScreenButton::Resume,
+            ScreenButton::NewGame,
+            ScreenButton::ToggleAssist,
+            ScreenButton::MainMenu,
+        ],
+        OVERLAY_COLOR,
+        &settings,
+    );
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    game: Res<Game>,
+    settings: Res<Settings>,
+) {
+    let title = if game.status() == Status::Won {
+        "You won!"
+    } else {
+        "No moves left"
+    };
+    spawn_screen(
+        &mut commands,
+        &assets,
+        title,
+        "",
+        &[ScreenButton::NewGame, ScreenButton::MainMenu],
+        OVERLAY_COLOR,
+        &settings,
+    );
+}
+
+fn spawn_statistics(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    settings: Res<Settings>,
+    statistics: Res<Statistics>,
+) {
+    spawn_screen(
+        &mut commands,
+        &assets,
+        "Statistics",
+        &statistics_text(&statistics),
+        &[ScreenButton::ResetStatistics, ScreenButton::MainMenu],
+        BACKGROUND_COLOR,
+        &settings,
+    );
+}
+
+fn spawn_settings(mut commands: Commands, assets: Res<AssetServer>, settings: Res<Settings>) {
+    spawn_screen(
+        &mut commands,
+        &assets,
+        "Settings",
+        "",
+        &[
+            ScreenButton::CycleDifficulty,
+            ScreenButton::CycleCustomSuits,
+            ScreenButton::ToggleColourRule,
+            ScreenButton::ToggleAnimations,
+            ScreenButton::CycleAnimationSpeed,
+            ScreenButton::CycleCardBack,
+            ScreenButton::ToggleAssist,
+            ScreenButton::CycleWindowSize,
+            ScreenButton::MainMenu,
+        ],
+        BACKGROUND_COLOR,
+        &settings,
+    );
+}
+
+/// Updates the statistics screen after the statistics are reset.
+fn update_statistics(statistics: Res<Statistics>, mut query: Query<&mut Text, With<ScreenBody>>) {
+    if !statistics.is_changed() {
+        return;
+    }
+    let value = statistics_text(&statistics);
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn despawn_screen(mut commands: Commands, query: Query<Entity, With<Screen>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn handle_screen_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut variant: ResMut<Variant>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings: ResMut<Settings>,
+    mut statistics: ResMut<Statistics>,
+    query: Query<(&Interaction, &ScreenButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let result = match button {
+            ScreenButton::Start(new_difficulty) => {
+                *variant = settings.variant;
+                *difficulty = *new_difficulty;
+                settings.difficulty = *new_difficulty;
+                state.set(AppState::Playing)
+            }
+            ScreenButton::CycleVariant => {
+                settings.variant = cycle(&Variant::all(), settings.variant);
+                Ok(())
+            }
+            ScreenButton::Resume => state.pop(),
+            ScreenButton::NewGame => state.replace(AppState::Playing),
+            ScreenButton::MainMenu => state.replace(AppState::Menu),
+            ScreenButton::ToggleAssist => {
+                settings.assist = !settings.assist;
+                Ok(())
+            }
+            ScreenButton::WatchReplay => state.replace(AppState::Replay),
+            ScreenButton::Statistics => state.replace(AppState::Statistics),
+            ScreenButton::ResetStatistics => {
+                statistics.reset();
+                save_statistics(&statistics);
+                Ok(())
+            }
+            ScreenButton::Settings => state.replace(AppState::Settings),
+            ScreenButton::CycleDifficulty => {
+                let mut difficulties = Difficulty::all().to_vec();
+                difficulties.push(settings.custom_difficulty);
+                settings.difficulty = cycle(&difficulties, settings.difficulty);
+                Ok(())
+            }
+            ScreenButton::CycleCustomSuits => {
+                let by_colour = settings.custom_difficulty.groups_by_colour();
+                let suits = cycle(&Suits::non_empty(), settings.custom_difficulty.suits());
+                settings.custom_difficulty = Difficulty::Custom { suits, by_colour };
+                Ok(())
+            }
+            ScreenButton::ToggleColourRule => {
+                let suits = settings.custom_difficulty.suits();
+                let by_colour = !settings.custom_difficulty.groups_by_colour();
+                settings.custom_difficulty = Difficulty::Custom { suits, by_colour };
+                Ok(())
+            }
+            ScreenButton::ToggleAnimations => {
+                settings.animations = !settings.animations;
+                Ok(())
+            }
+            ScreenButton::CycleAnimationSpeed => {
+                settings.animation_speed = cycle(&ANIMATION_SPEEDS, settings.animation_speed);
+                Ok(())
+            }
+            ScreenButton::CycleCardBack => {
+                settings.card_back = cycle(&CardBack::all(), settings.card_back);
+                Ok(())
+            }
+            ScreenButton::CycleWindowSize => {
+                let (width, height) = cycle(
+                    &WINDOW_SIZES,
+                    (settings.window_width, settings.window_height),
+                );
+                settings.window_width = width;
+                settings.window_height = height;
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            bevy::log::warn!("unable to handle {:?}: {:?}", button, e);
+        }
+    }
+}
+
+/// Updates the labels of buttons showing a setting when the setting changes.
+fn label_screen_buttons(
+    settings: Res<Settings>,
+    buttons: Query<(&ScreenButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = button.label(&settings);
+            }
+        }
+    }
+}
+
+fn highlight_screen_buttons(
+    mut query: Query<(&Interaction, &mut UiColor), (Changed<Interaction>, With<ScreenButton>)>,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::Hovered | Interaction::Clicked => BUTTON_HOVERED_COLOR.into(),
+            Interaction::None => BUTTON_COLOR.into(),
+        };
+    }
+}
@@ -0,0 +1,338 @@
+//! A selection cursor for playing without a mouse, driven by the keyboard or a gamepad.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Pile, Play};
+
+use super::hint::HintRequest;
+use super::layout::Layout;
+use crate::{find_max_rows, AppState, GameAction, GridPosition, LegalMoves, PlayingSystem, Shown};
+
+/// The distance the highlight extends past the edges of the focused and held cards.
+const HIGHLIGHT_WIDTH: f32 = 4.0;
+const FOCUS_COLOR: Color = Color::rgba(1.0, 0.85, 0.0, 0.45);
+const HELD_COLOR: Color = Color::rgba(0.2, 0.6, 1.0, 0.45);
+
+/// A plugin moving the selection cursor in response to [`SelectionInput`] events.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .add_event::<SelectionInput>()
+            .add_startup_system(spawn_highlights)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(reset_selection))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(
+                        keyboard_selection_input
+                            .label(PlayingSystem::Input)
+                            .after(PlayingSystem::GameOver),
+                    )
+                    .with_system(
+                        apply_selection_input
+                            .label(PlayingSystem::Selection)
+                            .after(PlayingSystem::Input),
+                    ),
+            )
+            .add_system(draw_highlights);
+    }
+}
+
+/// An input to the selection cursor, sent by devices without a pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionInput {
+    /// Focuses the column to the left.
+    Left,
+    /// Focuses the column to the right.
+    Right,
+    /// Focuses the card above in the same column.
+    Up,
+    /// Focuses the card below in the same column.
+    Down,
+    /// Picks up the run starting at the focused card, or drops the held run on the focused column.
+    Activate,
+    /// Deals the next available set.
+    Deal,
+    /// Plays the top card of the waste to the foundation, or onto the focused column when it
+    /// cannot go there.
+    PlayWaste,
+    /// Plays the card in the given free cell, or in the first free cell that has a legal play, to
+    /// the foundation, or onto the focused column when it cannot go there.
+    PlayCell(Option<usize>),
+    /// Plays the bottom card of the held run's column, or of the focused column, to the foundation.
+    PlayToFoundation,
+    /// Puts the bottom card of the held run's column, or of the focused column, into the first
+    /// empty free cell.
+    PlayToFreeCell,
+    /// Undoes the last move or deal.
+    Undo,
+    /// Points out a legal move.
+    Hint,
+}
+
+/// A resource holding the focused grid position and the run picked up by the selection cursor.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Whether the cursor is shown. It is hidden when the mouse is used.
+    pub active: bool,
+    /// The focused card, or the slot the held run would be dropped into.
+    pub focus: GridPosition,
+    /// The first card of the picked up run.
+    pub held: Option<GridPosition>,
+}
+
+/// The rows of a grid column the selection cursor can focus.
+#[derive(Debug, Clone, Copy)]
+struct ColumnRows {
+    /// The first shown row.
+    first_shown: u8,
+    /// The number of rows in the column.
+    len: u8,
+}
+
+impl ColumnRows {
+    /// Gets the extent of every column in the grid.
+    fn all(
+        game: &Game,
+        grid_cards: &HashMap<GridPosition, Entity>,
+        shown: &Query<&GridPosition, With<Shown>>,
+    ) -> Vec<ColumnRows> {
+        let mut columns = find_max_rows(grid_cards.keys(), game.columns().len())
+            .into_iter()
+            .map(|len| ColumnRows {
+                first_shown: len,
+                len,
+            })
+            .collect::<Vec<ColumnRows>>();
+        // shown cards of a previous game with more columns remain until they are despawned at the
+        // end of the frame
+        for position in shown.iter() {
+            if let Some(column) = columns.get_mut(position.x as usize) {
+                column.first_shown = column.first_shown.min(position.y);
+            }
+        }
+        columns
+    }
+
+    /// Clamps a row to one that can be focused in this column.
+    fn clamp(&self, row: u8, holding: bool) -> u8 {
+        if holding {
+            self.len
+        } else {
+            let last = self.len.saturating_sub(1);
+            row.clamp(self.first_shown.min(last), last)
+        }
+    }
+}
+
+/// A component marking the tint drawn over the focused or held card.
+/// It sits between the card and the one below it, so only the visible part of the card is tinted.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum Highlight {
+    Focus,
+    Held,
+}
+
+fn spawn_highlights(mut commands: Commands) {
+    for (highlight, color) in [
+        (Highlight::Focus, FOCUS_COLOR),
+        (Highlight::Held, HELD_COLOR),
+    ] {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite { color, ..default() },
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(highlight);
+    }
+}
+
+fn reset_selection(mut selection: ResMut<Selection>) {
+    *selection = Selection::default();
+}
+
+fn keyboard_selection_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut inputs: EventWriter<SelectionInput>,
+) {
+    for key in keyboard_input.get_just_pressed() {
+        let input = match key {
+            KeyCode::Left => SelectionInput::Left,
+            KeyCode::Right => SelectionInput::Right,
+            KeyCode::Up => SelectionInput::Up,
+            KeyCode::Down => SelectionInput::Down,
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => SelectionInput::Activate,
+            KeyCode::D => SelectionInput::Deal,
+            KeyCode::W => SelectionInput::PlayWaste,
+            KeyCode::F => SelectionInput::PlayToFoundation,
+            KeyCode::C => SelectionInput::PlayToFreeCell,
+            KeyCode::Key1 => SelectionInput::PlayCell(Some(0)),
+            KeyCode::Key2 => SelectionInput::PlayCell(Some(1)),
+            KeyCode::Key3 => SelectionInput::PlayCell(Some(2)),
+            KeyCode::Key4 => SelectionInput::PlayCell(Some(3)),
+            KeyCode::U | KeyCode::Back => SelectionInput::Undo,
+            KeyCode::H => SelectionInput::Hint,
+            _ => continue,
+        };
+        inputs.send(input);
+    }
+}
+
+fn apply_selection_input(
+    mut inputs: EventReader<SelectionInput>,
+    mut actions: EventWriter<GameAction>,
+    mut hints: EventWriter<HintRequest>,
+    mut selection: ResMut<Selection>,
+    mouse_input: Res<Input<MouseButton>>,
+    legal_moves: Res<LegalMoves>,
+    game: Res<Game>,
+    grid_cards: Res<HashMap<GridPosition, Entity>>,
+    shown: Query<&GridPosition, With<Shown>>,
+) {
+    if mouse_input.just_pressed(MouseButton::Left) && selection.active {
+        *selection = Selection::default();
+    }
+    let columns = ColumnRows::all(&game, &grid_cards, &shown);
+    let last_column = columns.len() as u8 - 1;
+    for input in inputs.iter() {
+        selection.active = true;
+        let focus = selection.focus;
+        match input {
+            SelectionInput::Left | SelectionInput::Right => {
+                let x = if *input == SelectionInput::Left {
+                    focus.x.saturating_sub(1)
+                } else {
+                    (focus.x + 1).min(last_column)
+                };
+                selection.focus = GridPosition {
+                    x,
+                    y: columns[x as usize].len.saturating_sub(1),
+                };
+            }
+            SelectionInput::Up => selection.focus.y = focus.y.saturating_sub(1),
+            SelectionInput::Down => selection.focus.y = focus.y + 1,
+            SelectionInput::Activate => match selection.held {
+                None if focus.y >= columns[focus.x as usize].first_shown
+                    && grid_cards.contains_key(&focus) =>
+                {
+                    selection.held = Some(focus);
+                }
+                None => bevy::log::trace!("not picking up hidden or missing card at {:?}", focus),
+                Some(held) if held.x == focus.x => {
+                    selection.held = None;
+                    selection.focus = held;
+                }
+                Some(held) => {
+                    if let Some((from, to)) = legal_moves
+                        .iter()
+                        .find(|legal_move| legal_move.0 == held && legal_move.1.x == focus.x)
+                        .copied()
+                    {
+                        actions.send(GameAction::Move(from, to));
+                        selection.held = None;
+                        selection.focus = to;
+                    } else {
+                        bevy::log::trace!("no legal move from {:?} to column {}", held, focus.x);
+                    }
+                }
+            },
+            SelectionInput::Deal => {
+                selection.held = None;
+                actions.send(GameAction::Deal);
+            }
+            SelectionInput::PlayWaste
+            | SelectionInput::PlayCell(_)
+            | SelectionInput::PlayToFoundation
+            | SelectionInput::PlayToFreeCell => {
+                let x = selection.held.map_or(focus.x, |held| held.x) as usize;
+                let sources = match *input {
+                    SelectionInput::PlayWaste => vec![Pile::Waste],
+                    SelectionInput::PlayCell(Some(cell)) => vec![Pile::Cell(cell)],
+                    SelectionInput::PlayCell(None) => {
+                        (0..game.free_cells().len()).map(Pile::Cell).collect()
+                    }
+                    _ => vec![],
+                };
+                let mut plays: Vec<Play> = sources
+                    .into_iter()
+                    .flat_map(|from| {
+                        [Pile::Foundation, Pile::Column(focus.x as usize)]
+                            .map(|to| Play { from, to })
+                    })
+                    .collect();
+                match *input {
+                    SelectionInput::PlayToFoundation => plays.push(Play {
+                        from: Pile::Column(x),
+                        to: Pile::Foundation,
+                    }),
+                    SelectionInput::PlayToFreeCell => {
+                        plays.extend(game.free_cells().iter().position(Option::is_none).map(
+                            |cell| Play {
+                                from: Pile::Column(x),
+                                to: Pile::Cell(cell),
+                            },
+                        ))
+                    }
+                    _ => {}
+                }
+                match plays
+                    .into_iter()
+                    .find(|play| game.check_play(*play).is_ok())
+                {
+                    Some(play) => {
+                        selection.held = None;
+                        actions.send(GameAction::Play(play));
+                    }
+                    None => bevy::log::trace!("no legal play for {:?}", input),
+                }
+            }
+            SelectionInput::Undo => {
+                selection.held = None;
+                actions.send(GameAction::Undo);
+            }
+            SelectionInput::Hint => hints.send(HintRequest),
+        }
+    }
+
+    // keep the cursor on the grid as cards are moved, dealt and collected
+    if let Some(held) = selection.held {
+        if !shown.iter().any(|position| *position == held) {
+            selection.held = None;
+        }
+    }
+    let holding = selection.held.is_some();
+    let column = columns[selection.focus.x.min(last_column) as usize];
+    let focus = GridPosition {
+        x: selection.focus.x.min(last_column),
+        y: column.clamp(selection.focus.y, holding),
+    };
+    if selection.focus != focus {
+        selection.focus = focus;
+    }
+}
+
+fn draw_highlights(
+    selection: Res<Selection>,
+    layout: Res<Layout>,
+    state: Res<State<AppState>>,
+    mut query: Query<(&Highlight, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let playing = *state.current() == AppState::Playing && selection.active;
+    for (highlight, mut transform, mut sprite, mut visibility) in query.iter_mut() {
+        let position = match highlight {
+            Highlight::Focus => Some(selection.focus),
+            Highlight::Held => selection.held,
+        };
+        visibility.is_visible = playing && position.is_some();
+        if let Some(position) = position {
+            transform.translation = layout
+                .grid_center(&position)
+                .extend(position.y as f32 + 0.5);
+            sprite.custom_size = Some(Vec2::splat(layout.card_size + HIGHLIGHT_WIDTH * 2.0));
+        }
+    }
+}
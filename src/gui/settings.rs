@@ -0,0 +1,117 @@
+//! Applying the player's settings to the app, and saving them when they change.
+
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+use spidertaire::settings::{CardBack, Settings};
+
+use super::animation::AnimationSettings;
+use super::assist::AssistSettings;
+use crate::{Available, Hidden};
+
+/// The time to wait after the last change before saving settings, in seconds.
+/// Dragging the edge of the window changes its size every frame, so saving is delayed until it settles.
+const SAVE_DELAY: f32 = 1.0;
+
+/// A plugin keeping the other settings resources in sync with [`Settings`], and saving it when changed.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(record_window_size)
+            .add_system(apply_settings.after(record_window_size))
+            .add_system(save_settings.after(record_window_size));
+    }
+}
+
+/// Loads the settings saved by previous runs, falling back to the defaults if they are missing or malformed.
+pub fn load_settings() -> Settings {
+    let path = match Settings::default_path() {
+        Some(path) => path,
+        None => return Settings::default(),
+    };
+    Settings::load(&path).unwrap_or_else(|e| {
+        bevy::log::warn!("unable to load settings from {:?}: {}", path, e);
+        Settings::default()
+    })
+}
+
+/// Gets the tint applied to the card back texture.
+pub fn card_back_color(card_back: CardBack) -> Color {
+    match card_back {
+        CardBack::Classic => Color::WHITE,
+        CardBack::Red => Color::rgb(1.0, 0.55, 0.55),
+        CardBack::Blue => Color::rgb(0.55, 0.7, 1.0),
+        CardBack::Green => Color::rgb(0.55, 1.0, 0.6),
+    }
+}
+
+/// Remembers the size of the window when the player resizes it.
+fn record_window_size(mut settings: ResMut<Settings>, mut resized: EventReader<WindowResized>) {
+    if let Some(e) = resized.iter().filter(|e| e.id.is_primary()).last() {
+        if settings.window_width != e.width || settings.window_height != e.height {
+            settings.window_width = e.width;
+            settings.window_height = e.height;
+        }
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut animation: ResMut<AnimationSettings>,
+    mut assist: ResMut<AssistSettings>,
+    mut windows: ResMut<Windows>,
+    mut card_backs: Query<&mut Sprite, Or<(With<Hidden>, With<Available>)>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let new_animation = AnimationSettings {
+        enabled: settings.animations,
+        speed: settings.animation_speed,
+    };
+    if *animation != new_animation {
+        *animation = new_animation;
+    }
+    if assist.enabled != settings.assist {
+        assist.enabled = settings.assist;
+    }
+    let color = card_back_color(settings.card_back);
+    for mut sprite in card_backs.iter_mut() {
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        if window.width() != settings.window_width || window.height() != settings.window_height {
+            window.set_resolution(settings.window_width, settings.window_height);
+        }
+    }
+}
+
+/// Saves the settings once they have stopped changing.
+fn save_settings(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut initialized: Local<bool>,
+    mut delay: Local<Option<Timer>>,
+) {
+    if settings.is_changed() {
+        // the settings are changed when first inserted, but there is nothing new to save
+        if *initialized {
+            *delay = Some(Timer::from_seconds(SAVE_DELAY, false));
+        }
+        *initialized = true;
+    }
+    let finished = delay
+        .as_mut()
+        .map_or(false, |timer| timer.tick(time.delta()).finished());
+    if finished {
+        *delay = None;
+        if let Some(path) = Settings::default_path() {
+            if let Err(e) = settings.save(&path) {
+                bevy::log::warn!("unable to save settings to {:?}: {}", path, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Recording the outcome of each game in the player's statistics.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Status};
+use spidertaire::stats::{Outcome, Statistics};
+use spidertaire::variant::Variant;
+
+use super::hud::{format_elapsed, Scoreboard};
+use crate::{AppState, Difficulty};
+
+/// A plugin loading the player's statistics and recording every game won, lost or abandoned.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_statistics())
+            .init_resource::<GameInProgress>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(start_game))
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(abandon_game))
+            .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(finish_game));
+    }
+}
+
+/// A resource holding the name the game being played is recorded under, until its outcome is recorded.
+#[derive(Debug, Default)]
+struct GameInProgress(Option<String>);
+
+/// Loads the statistics saved by previous runs, falling back to empty statistics.
+fn load_statistics() -> Statistics {
+    let path = match Statistics::default_path() {
+        Some(path) => path,
+        None => return Statistics::default(),
+    };
+    Statistics::load(&path).unwrap_or_else(|e| {
+        bevy::log::warn!("unable to load statistics from {:?}: {}", path, e);
+        Statistics::default()
+    })
+}
+
+/// Saves the statistics so they are kept for future runs.
+pub fn save_statistics(statistics: &Statistics) {
+    if let Some(path) = Statistics::default_path() {
+        if let Err(e) = statistics.save(&path) {
+            bevy::log::warn!("unable to save statistics to {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Formats the statistics of every difficulty, one difficulty per line.
+/// The preset Spider difficulties are always listed first, followed by every other variant and
+/// difficulty that has been played.
+pub fn statistics_text(statistics: &Statistics) -> String {
+    let mut keys = Difficulty::all()
+        .map(|difficulty| Statistics::key(Variant::Spider, difficulty))
+        .to_vec();
+    for key in statistics.difficulties.keys() {
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+    }
+    keys.into_iter()
+        .map(|key| {
+            let stats = statistics.get(&key);
+            format!(
+                "{}: {} played, {} won ({:.0}%), streak {} (best {}), best score {}, fastest win {}",
+                key,
+                stats.played,
+                stats.won,
+                stats.win_percentage(),
+                stats.current_streak,
+                stats.best_streak,
+                stats
+                    .best_score
+                    .map_or_else(|| String::from("-"), |score| score.to_string()),
+                stats.fastest_win_seconds.map_or_else(
+                    || String::from("-"),
+                    |seconds| format_elapsed(Duration::from_secs(seconds))
+                ),
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Records the game in progress as abandoned, if there is one.
+fn record_abandoned(game: &mut GameInProgress, statistics: &mut Statistics) {
+    if let Some(key) = game.0.take() {
+        bevy::log::info!("recording abandoned {} game", key);
+        statistics.record(&key, Outcome::Abandoned, 0, Duration::ZERO);
+        save_statistics(statistics);
+    }
+}
+
+/// Starts tracking a new game, abandoning any game that was still in progress.
+fn start_game(
+    variant: Res<Variant>,
+    difficulty: Res<Difficulty>,
+    mut game: ResMut<GameInProgress>,
+    mut statistics: ResMut<Statistics>,
+) {
+    record_abandoned(&mut game, &mut statistics);
+    game.0 = Some(Statistics::key(*variant, *difficulty));
+}
+
+fn abandon_game(mut game: ResMut<GameInProgress>, mut statistics: ResMut<Statistics>) {
+    record_abandoned(&mut game, &mut statistics);
+}
+
+/// Records a game that has been won or lost.
+fn finish_game(
+    scoreboard: Res<Scoreboard>,
+    game: Res<Game>,
+    mut in_progress: ResMut<GameInProgress>,
+    mut statistics: ResMut<Statistics>,
+) {
+    if let Some(key) = in_progress.0.take() {
+        let outcome = if game.status() == Status::Won {
+            Outcome::Won
+        } else {
+            Outcome::Lost
+        };
+        statistics.record(&key, outcome, game.score(), scoreboard.elapsed);
+        save_statistics(&statistics);
+    }
+}
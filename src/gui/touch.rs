@@ -0,0 +1,244 @@
+//! Touch controls for phones and tablets: tap to move, drag to move runs and long press for a hint.
+
+use bevy::prelude::*;
+
+use spidertaire::game::{Game, Pile};
+
+use super::animation::MoveTween;
+use super::hint::HintRequest;
+use super::layout::Layout;
+use super::picking::{pick, Pickable};
+use super::piles::pile_at;
+use crate::{
+    find_auto_action, find_pile_play, AppState, Available, GameAction, GridPosition, LegalMoves,
+    PlayingSystem, Shown,
+};
+
+/// The time a touch must be held in place to ask for a hint, in seconds.
+const LONG_PRESS_DURATION: f64 = 0.5;
+/// The distance a touch must move to start dragging, relative to the card size.
+const DRAG_THRESHOLD_RATIO: f32 = 0.15;
+/// The time after a touch during which mouse presses are ignored, in seconds.
+/// Browsers follow a tap with emulated mouse events, which would otherwise apply the tap twice.
+const MOUSE_EMULATION_WINDOW: f64 = 1.0;
+/// The distance dragged cards are raised towards the camera, so they pass over resting cards.
+const DRAG_Z: f32 = 200.0;
+
+/// A plugin turning touch gestures into [`GameAction`] and [`HintRequest`] events.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastTouch>()
+            .init_resource::<Option<Gesture>>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(cancel_gesture))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(
+                        touch_input
+                            .label(PlayingSystem::Input)
+                            .after(PlayingSystem::GameOver),
+                    )
+                    .with_system(drag_runs.after(PlayingSystem::Input)),
+            );
+    }
+}
+
+/// A component marking a grid card being dragged by a touch.
+#[derive(Component, Debug)]
+pub struct Dragged;
+
+/// A resource holding when the screen was last touched, in seconds since startup.
+#[derive(Debug, Default)]
+pub struct LastTouch(Option<f64>);
+
+impl LastTouch {
+    /// Determines whether a mouse press may have been emulated by the browser after a touch.
+    pub fn suppresses_mouse(&self, time: &Time) -> bool {
+        self.0.map_or(false, |last| {
+            time.seconds_since_startup() - last < MOUSE_EMULATION_WINDOW
+        })
+    }
+}
+
+/// What was under a touch when it started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// A shown grid card, and the run below it.
+    Run(GridPosition),
+    /// The stock.
+    Stock,
+    /// The top card of the waste or a card in a free cell.
+    Pile(Pile),
+    /// Anything else, such as a hidden card or the table.
+    Nothing,
+}
+
+/// The touch currently being followed, held in an `Option<Gesture>` resource.
+#[derive(Debug, Clone, Copy)]
+struct Gesture {
+    id: u64,
+    target: Target,
+    /// Where the touch started, in world coordinates.
+    start: Vec2,
+    /// Where the touch is now, in world coordinates.
+    current: Vec2,
+    /// When the touch started, in seconds since startup.
+    started_at: f64,
+    dragging: bool,
+    hinted: bool,
+}
+
+fn cancel_gesture(
+    mut commands: Commands,
+    mut gesture: ResMut<Option<Gesture>>,
+    dragged: Query<Entity, With<Dragged>>,
+) {
+    *gesture = None;
+    for e in dragged.iter() {
+        commands.entity(e).remove::<Dragged>();
+    }
+}
+
+/// Follows a single touch, deciding whether it is a tap, a drag or a long press.
+#[allow(clippy::too_many_arguments)]
+fn touch_input(
+    mut commands: Commands,
+    time: Res<Time>,
+    touches: Res<Touches>,
+    layout: Res<Layout>,
+    game: Res<Game>,
+    legal_moves: Res<LegalMoves>,
+    available_sets: Res<Vec<Available>>,
+    mut gesture: ResMut<Option<Gesture>>,
+    mut last_touch: ResMut<LastTouch>,
+    mut actions: EventWriter<GameAction>,
+    mut hints: EventWriter<HintRequest>,
+    shown: Query<(Entity, &GridPosition), With<Shown>>,
+    dragged: Query<Entity, With<Dragged>>,
+) {
+    let now = time.seconds_since_startup();
+    if touches.iter().next().is_some() {
+        last_touch.0 = Some(now);
+    }
+
+    if gesture.is_none() {
+        if let Some(touch) = touches.iter_just_pressed().next() {
+            let start = layout.touch_world_position(touch.position());
+            let picked = pick(
+                shown.iter().map(|(_, position)| Pickable {
+                    item: *position,
+                    bounds: layout.card_bounds(layout.grid_center(position)),
+                    depth: position.y as f32,
+                }),
+                start,
+            );
+            let target = match picked {
+                Some(position) => Target::Run(position),
+                // tapping the empty stock turns the waste over
+                None if !(available_sets.is_empty() && game.waste().is_empty())
+                    && layout
+                        .available_bounds(available_sets.len().max(1))
+                        .contains(start) =>
+                {
+                    Target::Stock
+                }
+                None => pile_at(&game, &layout, start).map_or(Target::Nothing, Target::Pile),
+            };
+            *gesture = Some(Gesture {
+                id: touch.id(),
+                target,
+                start,
+                current: start,
+                started_at: now,
+                dragging: false,
+                hinted: false,
+            });
+        }
+    }
+
+    let current = match *gesture {
+        Some(current) => current,
+        None => return,
+    };
+    if let Some(touch) = touches.get_pressed(current.id) {
+        let position = layout.touch_world_position(touch.position());
+        let mut updated = Gesture {
+            current: position,
+            ..current
+        };
+        let moved = position.distance(current.start) > layout.card_size * DRAG_THRESHOLD_RATIO;
+        if let (Target::Run(from), false, true) = (current.target, current.dragging, moved) {
+            updated.dragging = true;
+            for (e, position) in shown.iter() {
+                if position.x == from.x && position.y >= from.y {
+                    commands.entity(e).remove::<MoveTween>().insert(Dragged);
+                }
+            }
+        }
+        if !updated.dragging
+            && !moved
+            && !current.hinted
+            && now - current.started_at >= LONG_PRESS_DURATION
+        {
+            updated.hinted = true;
+            hints.send(HintRequest);
+        }
+        *gesture = Some(updated);
+        return;
+    }
+
+    let released = touches
+        .iter_just_released()
+        .find(|touch| touch.id() == current.id);
+    if let Some(touch) = released {
+        let end = layout.touch_world_position(touch.position());
+        match current.target {
+            Target::Run(from) if current.dragging => {
+                let dropped = layout.grid_column(end.x).and_then(|x| {
+                    legal_moves
+                        .iter()
+                        .find(|legal_move| legal_move.0 == from && legal_move.1.x == x)
+                        .copied()
+                });
+                if let Some((from, to)) = dropped {
+                    actions.send(GameAction::Move(from, to));
+                }
+            }
+            _ if current.hinted => {}
+            Target::Run(from) => {
+                if let Some(action) = find_auto_action(&game, &legal_moves, from) {
+                    actions.send(action);
+                }
+            }
+            Target::Stock => actions.send(GameAction::Deal),
+            Target::Pile(pile) => {
+                if let Some(play) = find_pile_play(&game, pile) {
+                    actions.send(GameAction::Play(play));
+                }
+            }
+            Target::Nothing => {}
+        }
+    } else {
+        bevy::log::trace!("touch {} was cancelled", current.id);
+    }
+    *gesture = None;
+    for e in dragged.iter() {
+        commands.entity(e).remove::<Dragged>();
+    }
+}
+
+/// Moves dragged cards along with the touch dragging them.
+fn drag_runs(
+    layout: Res<Layout>,
+    gesture: Res<Option<Gesture>>,
+    mut query: Query<(&GridPosition, &mut Transform), With<Dragged>>,
+) {
+    if let Some(gesture) = *gesture {
+        let offset = gesture.current - gesture.start;
+        for (position, mut transform) in query.iter_mut() {
+            transform.translation =
+                (layout.grid_center(position) + offset).extend(position.y as f32 + DRAG_Z);
+        }
+    }
+}
@@ -2,11 +2,24 @@
 #![deny(clippy::all)]
 //! Defines structures representing cards and collections of cards.
 
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod game;
+pub mod replay;
+pub mod settings;
+pub mod stats;
+pub mod storage;
+pub mod variant;
 
-/// The value of a card.
+/// The value of a card. Values are ordered with the ace low, see [`CardValue::cmp_with`] to rank
+/// it high instead.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CardValue {
     K,
     Q,
@@ -24,23 +37,73 @@ pub enum CardValue {
 }
 
 impl CardValue {
-    /// Creates an array of all card values in descending order.
-    pub fn all() -> [CardValue; 13] {
+    /// Iterates over all card values in ascending order, from the ace to the king. Runs are built
+    /// in the reverse order.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(CardValue::iter().next(), Some(CardValue::A));
+    /// assert_eq!(CardValue::iter().rev().next(), Some(CardValue::K));
+    /// assert_eq!(CardValue::iter().len(), 13);
+    /// ```
+    pub fn iter() -> impl DoubleEndedIterator<Item = CardValue> + ExactSizeIterator {
         [
-            CardValue::K,
-            CardValue::Q,
-            CardValue::J,
-            CardValue::Ten,
-            CardValue::Nine,
-            CardValue::Eight,
-            CardValue::Seven,
-            CardValue::Six,
-            CardValue::Five,
-            CardValue::Four,
-            CardValue::Three,
-            CardValue::Two,
             CardValue::A,
+            CardValue::Two,
+            CardValue::Three,
+            CardValue::Four,
+            CardValue::Five,
+            CardValue::Six,
+            CardValue::Seven,
+            CardValue::Eight,
+            CardValue::Nine,
+            CardValue::Ten,
+            CardValue::J,
+            CardValue::Q,
+            CardValue::K,
         ]
+        .into_iter()
+    }
+
+    /// Gets the index of the card value in ascending order with the ace low, from 0 to 12.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(CardValue::A.index(), 0);
+    /// assert_eq!(CardValue::K.index(), 12);
+    /// assert_eq!(CardValue::from_index(9), Some(CardValue::Ten));
+    /// assert_eq!(CardValue::from_index(13), None);
+    /// ```
+    pub fn index(&self) -> usize {
+        usize::from(u8::from(*self)) - 1
+    }
+
+    /// Gets the card value at the given index from [`CardValue::index`].
+    pub fn from_index(index: usize) -> Option<CardValue> {
+        Self::iter().nth(index)
+    }
+
+    /// Gets the rank of the card value, counting the ace as 1 when it is low or 14 when it is high.
+    pub fn rank(&self, ace: Ace) -> u8 {
+        match (self, ace) {
+            (CardValue::A, Ace::High) => 14,
+            _ => u8::from(*self),
+        }
+    }
+
+    /// Compares two card values by rank, with the ace high or low.
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use spidertaire::*;
+    ///
+    /// assert!(CardValue::A < CardValue::Two);
+    /// assert_eq!(CardValue::A.cmp_with(&CardValue::K, Ace::High), Ordering::Greater);
+    /// ```
+    pub fn cmp_with(&self, other: &CardValue, ace: Ace) -> Ordering {
+        self.rank(ace).cmp(&other.rank(ace))
     }
 
     /// Returns the string representation of the card value.
@@ -115,6 +178,27 @@ impl CardValue {
     }
 }
 
+impl PartialOrd for CardValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CardValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with(other, Ace::Low)
+    }
+}
+
+/// Whether an ace ranks below the two or above the king when card values are compared.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Ace {
+    #[default]
+    Low,
+    High,
+}
+
 /// ```
 /// use spidertaire::*;
 ///
@@ -179,9 +263,9 @@ impl TryFrom<u8> for CardValue {
     }
 }
 
-/// The suit of a card.
+/// The suit of a card. Suits are ordered as in [`CardSuit::iter`].
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum CardSuit {
     Hearts,
     Diamonds,
@@ -190,14 +274,49 @@ pub enum CardSuit {
 }
 
 impl CardSuit {
-    /// Creates an array of all card suits.
-    pub fn all() -> [CardSuit; 4] {
+    /// Iterates over all card suits in order: hearts, diamonds, clubs and spades.
+    pub fn iter() -> impl DoubleEndedIterator<Item = CardSuit> + ExactSizeIterator {
         [
             CardSuit::Hearts,
             CardSuit::Diamonds,
             CardSuit::Clubs,
             CardSuit::Spades,
         ]
+        .into_iter()
+    }
+
+    /// Gets the index of the card suit in [`CardSuit::iter`], from 0 to 3.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(CardSuit::Hearts.index(), 0);
+    /// assert_eq!(CardSuit::from_index(3), Some(CardSuit::Spades));
+    /// assert_eq!(CardSuit::from_index(4), None);
+    /// ```
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Gets the card suit at the given index from [`CardSuit::index`].
+    pub fn from_index(index: usize) -> Option<CardSuit> {
+        Self::iter().nth(index)
+    }
+
+    /// Returns the letter standing for the card suit, used where the suit symbols cannot be typed.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(CardSuit::Clubs.letter(), 'C');
+    /// ```
+    pub fn letter(&self) -> char {
+        match self {
+            CardSuit::Hearts => 'H',
+            CardSuit::Diamonds => 'D',
+            CardSuit::Clubs => 'C',
+            CardSuit::Spades => 'S',
+        }
     }
 
     /// Returns the string representation of the card suit.
@@ -209,10 +328,151 @@ impl CardSuit {
             CardSuit::Spades => "\u{2660}",
         }
     }
+
+    /// Returns the colour of the card suit.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(CardSuit::Diamonds.color(), CardColor::Red);
+    /// assert_eq!(CardSuit::Clubs.color(), CardColor::Black);
+    /// ```
+    pub fn color(&self) -> CardColor {
+        match self {
+            CardSuit::Hearts | CardSuit::Diamonds => CardColor::Red,
+            CardSuit::Clubs | CardSuit::Spades => CardColor::Black,
+        }
+    }
+
+    /// Determines whether the card suit is hearts or diamonds.
+    pub fn is_red(&self) -> bool {
+        self.color() == CardColor::Red
+    }
+
+    /// Determines whether the card suit is clubs or spades.
+    pub fn is_black(&self) -> bool {
+        self.color() == CardColor::Black
+    }
+
+    /// Determines whether two suits are the same colour.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert!(CardSuit::Spades.same_color(CardSuit::Clubs));
+    /// assert!(!CardSuit::Spades.same_color(CardSuit::Hearts));
+    /// ```
+    pub fn same_color(&self, other: CardSuit) -> bool {
+        self.color() == other.color()
+    }
 }
 
-/// A playing card.
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// The colour of a card suit.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardColor {
+    Red,
+    Black,
+}
+
+/// A set of card suits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Suits(u8);
+
+impl Suits {
+    /// Creates a set of the given suits.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let suits = Suits::new([CardSuit::Spades, CardSuit::Clubs, CardSuit::Spades]);
+    /// assert_eq!(suits.len(), 2);
+    /// assert!(suits.contains(CardSuit::Clubs));
+    /// assert!(!suits.contains(CardSuit::Hearts));
+    /// assert_eq!(suits.iter().collect::<Vec<CardSuit>>(), [CardSuit::Clubs, CardSuit::Spades]);
+    /// ```
+    pub fn new<I: IntoIterator<Item = CardSuit>>(suits: I) -> Self {
+        Suits(suits.into_iter().fold(0, |bits, suit| bits | Self::bit(suit)))
+    }
+
+    /// Creates the set of every suit.
+    pub fn all() -> Self {
+        Self::new(CardSuit::iter())
+    }
+
+    /// Gets the bit standing for a suit.
+    fn bit(suit: CardSuit) -> u8 {
+        1 << suit.index()
+    }
+
+    /// Determines whether the set contains a suit.
+    pub fn contains(&self, suit: CardSuit) -> bool {
+        self.0 & Self::bit(suit) != 0
+    }
+
+    /// Gets the number of suits in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Determines whether the set has no suits.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates over the suits in the set, in the order of [`CardSuit::iter`].
+    pub fn iter(&self) -> impl Iterator<Item = CardSuit> + '_ {
+        CardSuit::iter().filter(|suit| self.contains(*suit))
+    }
+
+    /// Lists every set with at least one suit, from the sets of one suit up to the set of every suit.
+    pub fn non_empty() -> Vec<Suits> {
+        let mut sets = (1..16).map(Suits).collect::<Vec<Suits>>();
+        sets.sort_by_key(Suits::len);
+        sets
+    }
+}
+
+/// Writes the letters of the suits in the set in the order of [`CardSuit::iter`], like `HCS`.
+impl fmt::Display for Suits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for suit in self.iter() {
+            write!(f, "{}", suit.letter())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the letters of the suits in a set, ignoring case.
+///
+/// ```
+/// use spidertaire::*;
+///
+/// assert_eq!("sh".parse(), Ok(Suits::new([CardSuit::Spades, CardSuit::Hearts])));
+/// assert!("sx".parse::<Suits>().is_err());
+/// assert!("".parse::<Suits>().is_err());
+/// ```
+impl FromStr for Suits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suits = s
+            .chars()
+            .map(|letter| {
+                CardSuit::iter()
+                    .find(|suit| suit.letter().eq_ignore_ascii_case(&letter))
+                    .ok_or(format!("{} is not a suit, expected one of S, H, D or C", letter))
+            })
+            .collect::<Result<Vec<CardSuit>, String>>()?;
+        if suits.is_empty() {
+            return Err(String::from("at least one suit is needed"));
+        }
+        Ok(Suits::new(suits))
+    }
+}
+
+/// A playing card. Cards are ordered by value with the ace low, and then by suit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Card {
     /// The value of the card.
     pub value: CardValue,
@@ -220,6 +480,91 @@ pub struct Card {
     pub suit: CardSuit,
 }
 
+impl Card {
+    /// The number of distinct cards, one of each value in each suit.
+    pub const COUNT: usize = 52;
+
+    /// Iterates over every distinct card in order, from the ace of hearts to the king of spades.
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use spidertaire::*;
+    ///
+    /// let cards: HashSet<Card> = Card::iter().collect();
+    /// assert_eq!(cards.len(), Card::COUNT);
+    /// assert!(Card::iter().zip(Card::iter().skip(1)).all(|(card, next)| card < next));
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Card> {
+        CardValue::iter().flat_map(|value| CardSuit::iter().map(move |suit| Card { value, suit }))
+    }
+
+    /// Gets the dense index of the card, from 0 to 51, following the order of cards.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let card = Card { value: CardValue::Seven, suit: CardSuit::Clubs };
+    /// assert_eq!(Card::from_index(card.index()), Some(card));
+    /// assert!(Card::iter().enumerate().all(|(index, card)| card.index() == index));
+    /// assert_eq!(Card::from_index(Card::COUNT), None);
+    /// ```
+    pub fn index(&self) -> usize {
+        self.value.index() * CardSuit::iter().len() + self.suit.index()
+    }
+
+    /// Gets the card at the given index from [`Card::index`].
+    pub fn from_index(index: usize) -> Option<Card> {
+        let suits = CardSuit::iter().len();
+        Some(Card {
+            value: CardValue::from_index(index / suits)?,
+            suit: CardSuit::from_index(index % suits)?,
+        })
+    }
+
+    /// Gets the colour of the card's suit.
+    pub fn color(&self) -> CardColor {
+        self.suit.color()
+    }
+
+    /// Determines whether the card can follow another in a descending run, being one lower in any suit.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let eight = Card { value: CardValue::Eight, suit: CardSuit::Hearts };
+    /// let nine = Card { value: CardValue::Nine, suit: CardSuit::Spades };
+    /// assert!(eight.is_next_in_run(&nine));
+    /// assert!(!nine.is_next_in_run(&eight));
+    /// ```
+    pub fn is_next_in_run(&self, other: &Card) -> bool {
+        other.value.next() == Some(self.value)
+    }
+
+    /// Determines whether the card follows another in a descending run of one suit.
+    pub fn is_next_in_suit(&self, other: &Card) -> bool {
+        self.suit == other.suit && self.is_next_in_run(other)
+    }
+
+    /// Determines whether the card follows another in a descending run of one colour.
+    pub fn is_next_in_color(&self, other: &Card) -> bool {
+        self.suit.same_color(other.suit) && self.is_next_in_run(other)
+    }
+
+    /// Determines whether the card follows another in a descending run of alternating colours.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let nine = Card { value: CardValue::Nine, suit: CardSuit::Spades };
+    /// let eight = |suit| Card { value: CardValue::Eight, suit };
+    /// assert!(eight(CardSuit::Diamonds).is_next_alternating(&nine));
+    /// assert!(!eight(CardSuit::Clubs).is_next_alternating(&nine));
+    /// ```
+    pub fn is_next_alternating(&self, other: &Card) -> bool {
+        !self.suit.same_color(other.suit) && self.is_next_in_run(other)
+    }
+}
+
 /// A 52-card deck.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Deck {
@@ -249,7 +594,7 @@ impl Deck {
     pub fn from_suit(suit: CardSuit) -> Deck {
         let mut cards = Vec::with_capacity(52);
         for _ in 0..4 {
-            for value in CardValue::all() {
+            for value in CardValue::iter().rev() {
                 cards.push(Card { value, suit });
             }
         }
@@ -270,10 +615,10 @@ impl Deck {
     pub fn from_suits(suit1: CardSuit, suit2: CardSuit) -> Deck {
         let mut cards = Vec::with_capacity(52);
         for _ in 0..2 {
-            for value in CardValue::all() {
+            for value in CardValue::iter().rev() {
                 cards.push(Card { value, suit: suit1 });
             }
-            for value in CardValue::all() {
+            for value in CardValue::iter().rev() {
                 cards.push(Card { value, suit: suit2 });
             }
         }
@@ -299,6 +644,23 @@ impl Deck {
         self.cards.shuffle(&mut thread_rng());
     }
 
+    /// Orders the cards in the deck randomly but reproducibly, so the same seed always gives the same order.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let mut deck = Deck::new();
+    /// let mut other = Deck::new();
+    /// deck.shuffle_seeded(42);
+    /// other.shuffle_seeded(42);
+    /// assert_eq!(deck, other);
+    /// other.shuffle_seeded(7);
+    /// assert_ne!(deck, other);
+    /// ```
+    pub fn shuffle_seeded(&mut self, seed: u64) {
+        self.cards.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+
     /// Merges the cards from deck2 into this deck.
     /// 
     /// ```
@@ -316,11 +678,221 @@ impl Deck {
 impl Default for Deck {
     fn default() -> Self {
         let mut cards = Vec::with_capacity(52);
-        for suit in CardSuit::all() {
-            for value in CardValue::all() {
+        for suit in CardSuit::iter() {
+            for value in CardValue::iter().rev() {
                 cards.push(Card { value, suit });
             }
         }
         Self { cards }
     }
 }
+
+/// An enumeration of the available difficulties.
+/// Determines the suits in play, and whether runs may be grouped by colour rather than suit.
+/// Saved as its name, like `"Hard"` or `"Custom HCS by colour"`.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Difficulty {
+    #[default]
+    Easy,
+    Medium,
+    Hard,
+    /// Any set of suits. When `by_colour` is set, runs of one colour can be moved together, as in
+    /// Tarantula, though only runs of one suit are completed.
+    Custom { suits: Suits, by_colour: bool },
+}
+
+impl Difficulty {
+    /// Creates an array of the preset difficulties in ascending order.
+    pub fn all() -> [Difficulty; 3] {
+        [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+    }
+
+    /// Returns the display name of the difficulty, or `Custom` for any custom difficulty.
+    /// The full name of a custom difficulty is given by its [`Display`](fmt::Display) implementation.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Custom { .. } => "Custom",
+        }
+    }
+
+    /// Gets the suits in play. A custom difficulty without any suits plays with spades.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(Difficulty::Medium.suits(), Suits::new([CardSuit::Spades, CardSuit::Hearts]));
+    /// assert_eq!(Difficulty::Hard.suits(), Suits::all());
+    ///
+    /// let empty = Difficulty::Custom { suits: Suits::new([]), by_colour: false };
+    /// assert_eq!(empty.suits(), Suits::new([CardSuit::Spades]));
+    /// assert_eq!(empty.decks(2), Difficulty::Easy.decks(2));
+    /// ```
+    pub fn suits(&self) -> Suits {
+        match self {
+            Difficulty::Easy => Suits::new([CardSuit::Spades]),
+            Difficulty::Medium => Suits::new([CardSuit::Spades, CardSuit::Hearts]),
+            Difficulty::Hard => Suits::all(),
+            Difficulty::Custom { suits, .. } if suits.is_empty() => Suits::new([CardSuit::Spades]),
+            Difficulty::Custom { suits, .. } => *suits,
+        }
+    }
+
+    /// Determines whether runs of one colour can be moved together, rather than only runs of one suit.
+    pub fn groups_by_colour(&self) -> bool {
+        matches!(self, Difficulty::Custom { by_colour: true, .. })
+    }
+
+    /// Creates the two decks played with at this difficulty, unshuffled.
+    /// Easy uses only spades, medium uses spades and hearts, and hard uses every suit.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let deck = Difficulty::Medium.deck();
+    /// assert_eq!(deck.cards.len(), 104);
+    /// assert!(deck.cards.iter().all(|card| card.suit == CardSuit::Spades || card.suit == CardSuit::Hearts));
+    /// ```
+    pub fn deck(&self) -> Deck {
+        self.decks(2)
+    }
+
+    /// Creates the given number of decks played with at this difficulty, unshuffled.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!(Difficulty::Hard.decks(1), Deck::new());
+    /// assert_eq!(Difficulty::Easy.decks(3).cards.len(), 156);
+    /// ```
+    ///
+    /// A custom difficulty has a run of thirteen cards for every suit of every deck, taking its
+    /// suits in turn, so the suits are as even as the number of runs allows.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// let suits = Suits::new([CardSuit::Spades, CardSuit::Hearts, CardSuit::Clubs]);
+    /// let deck = Difficulty::Custom { suits, by_colour: false }.decks(2);
+    /// assert_eq!(deck.cards.len(), 104);
+    /// let count = |suit| deck.cards.iter().filter(|card| card.suit == suit).count();
+    /// assert_eq!(count(CardSuit::Hearts), 39);
+    /// assert_eq!(count(CardSuit::Clubs), 39);
+    /// assert_eq!(count(CardSuit::Spades), 26);
+    /// assert_eq!(count(CardSuit::Diamonds), 0);
+    /// ```
+    pub fn decks(&self, count: usize) -> Deck {
+        let single = match self {
+            Difficulty::Easy => Deck::from_suit(CardSuit::Spades),
+            Difficulty::Medium => Deck::from_suits(CardSuit::Spades, CardSuit::Hearts),
+            Difficulty::Hard => Deck::new(),
+            Difficulty::Custom { .. } => {
+                let suits = self.suits().iter().collect::<Vec<CardSuit>>();
+                let runs = count * CardSuit::iter().len();
+                let cards = (0..runs)
+                    .flat_map(|run| {
+                        let suit = suits[run % suits.len()];
+                        CardValue::iter().rev().map(move |value| Card { value, suit })
+                    })
+                    .collect();
+                return Deck { cards };
+            }
+        };
+        let mut deck = Deck { cards: Vec::new() };
+        for _ in 0..count {
+            deck.combine(single.clone());
+        }
+        deck
+    }
+}
+
+/// Writes the name of the difficulty. Custom difficulties are written with the letters of the
+/// suits in play, like `Custom HCS` or `Custom HCS by colour`, so the name can always be parsed.
+///
+/// ```
+/// use spidertaire::*;
+///
+/// let empty = Difficulty::Custom { suits: Suits::new([]), by_colour: true };
+/// assert_eq!(empty.to_string(), "Custom S by colour");
+/// let parsed: Difficulty = empty.to_string().parse().unwrap();
+/// assert_eq!(parsed.suits(), empty.suits());
+/// assert_eq!(parsed.to_string(), empty.to_string());
+/// ```
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Custom { by_colour, .. } => {
+                write!(f, "{} {}", self.as_str(), self.suits())?;
+                if *by_colour {
+                    write!(f, " by colour")?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parses a difficulty from its name, ignoring case. Custom difficulties are written like
+    /// `custom shc`, with the words optionally separated by dashes instead of spaces, and end in
+    /// `by colour` when runs are grouped by colour.
+    ///
+    /// ```
+    /// use spidertaire::*;
+    ///
+    /// assert_eq!("hard".parse::<Difficulty>(), Ok(Difficulty::Hard));
+    /// assert!("expert".parse::<Difficulty>().is_err());
+    ///
+    /// let suits = Suits::new([CardSuit::Spades, CardSuit::Hearts, CardSuit::Clubs]);
+    /// let custom = Difficulty::Custom { suits, by_colour: true };
+    /// assert_eq!(custom.to_string(), "Custom HCS by colour");
+    /// assert_eq!("custom-shc-by-color".parse(), Ok(custom));
+    /// assert_eq!(custom.to_string().parse(), Ok(custom));
+    /// assert!("custom".parse::<Difficulty>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s
+            .split(|c: char| c.is_whitespace() || c == '-')
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<String>>();
+        match words.as_slice() {
+            [custom, suits, rest @ ..] if custom == "custom" => {
+                let by_colour = match rest {
+                    [] => false,
+                    [by, colour] if by == "by" && (colour == "colour" || colour == "color") => true,
+                    _ => return Err(format!("{} is not a difficulty", s)),
+                };
+                Ok(Difficulty::Custom {
+                    suits: suits.parse()?,
+                    by_colour,
+                })
+            }
+            _ => Difficulty::all()
+                .into_iter()
+                .find(|difficulty| difficulty.as_str().eq_ignore_ascii_case(s.trim()))
+                .ok_or(format!("{} is not a difficulty", s)),
+        }
+    }
+}
+
+impl From<Difficulty> for String {
+    fn from(difficulty: Difficulty) -> Self {
+        difficulty.to_string()
+    }
+}
+
+impl TryFrom<String> for Difficulty {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
@@ -1,20 +1,105 @@
 #![deny(missing_docs)]
 //! An implementation of the card game Spider Solitaire.
 
+mod cli;
+mod gui;
+
 use std::collections::HashMap;
+use std::env;
+use std::process;
 
 use bevy::prelude::*;
 
-use spidertaire::{Card, CardSuit, Deck};
+use spidertaire::game::{Action, Game, Move, Pile, Play, Status};
+use spidertaire::settings::Settings;
+use spidertaire::variant::Variant;
+use spidertaire::{Card, CardColor, CardSuit, CardValue, Difficulty};
+
+use gui::animation::{AnimationPlugin, AnimationSettings, MoveTween};
+use gui::assist::AssistPlugin;
+use gui::gamepad::GamepadPlugin;
+use gui::hint::HintPlugin;
+use gui::hud::HudPlugin;
+use gui::layout::{Layout, LayoutPlugin};
+use gui::picking::{pick, Pickable};
+use gui::piles::PilesPlugin;
+use gui::replay::{ReplayPlugin, ReplaySource};
+use gui::screens::ScreensPlugin;
+use gui::selection::SelectionPlugin;
+use gui::settings::{card_back_color, load_settings, SettingsPlugin};
+use gui::stats::StatsPlugin;
+use gui::touch::{Dragged, LastTouch, TouchPlugin};
+
+/// The states the application can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AppState {
+    /// The main menu, shown on launch and after leaving a game.
+    Menu,
+    /// A game is in progress.
+    Playing,
+    /// A game is in progress but suspended behind the pause screen.
+    Paused,
+    /// The game has been won or no moves remain.
+    GameOver,
+    /// The player's statistics are shown, reached from the main menu.
+    Statistics,
+    /// The settings can be changed, reached from the main menu.
+    Settings,
+    /// A recorded game is played back, reached from the main menu.
+    Replay,
+}
+
+/// Labels used to order the systems that run while playing.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+enum PlayingSystem {
+    LegalMoves,
+    GameOver,
+    Input,
+    Selection,
+    Action,
+    Collect,
+}
+
+/// An event requesting a change to the game, sent by the input systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameAction {
+    /// Moves the card at the first position, along with every card below it, to the second position.
+    Move(GridPosition, GridPosition),
+    /// Plays a single card from the waste or to the foundation.
+    Play(Play),
+    /// Deals the next available set onto the grid.
+    Deal,
+    /// Restores the game to how it was before the last move or deal.
+    Undo,
+}
+
+impl GameAction {
+    /// Gets the action performed on the game.
+    pub fn action(&self) -> Action {
+        match *self {
+            GameAction::Move(from, to) => Action::Move(Move {
+                from: from.x as usize,
+                row: from.y as usize,
+                to: to.x as usize,
+            }),
+            GameAction::Play(play) => Action::Play(play),
+            GameAction::Deal => Action::Deal,
+            GameAction::Undo => Action::Undo,
+        }
+    }
 
-/// An enumeration of the available difficulties.
-/// Determines the number of suits in play.
-#[allow(dead_code)] // TODO: determine level based on cache/previous game/user choice
-#[derive(Debug, Clone, Copy)]
-enum Difficulty {
-    Easy,
-    Medium,
-    Hard,
+    /// Creates the event performing an action on the game.
+    pub fn from_action(action: Action, game: &Game) -> Self {
+        match action {
+            Action::Move(legal_move) => {
+                let (from, to) = grid_move(game, legal_move);
+                GameAction::Move(from, to)
+            }
+            Action::Play(play) => GameAction::Play(play),
+            Action::Deal => GameAction::Deal,
+            Action::Undo => GameAction::Undo,
+        }
+    }
 }
 
 /// A component representing a card.
@@ -31,8 +116,32 @@ struct Hidden;
 #[derive(Component)]
 struct Shown;
 
+/// A component representing the text drawn in the top corners of a shown card.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum CardLabel {
+    Value,
+    Suit,
+}
+
+impl CardLabel {
+    /// Gets the position of the label relative to the centre of its card.
+    pub fn translation(&self, layout: &Layout) -> Vec3 {
+        let half_size = layout.card_size / 2.0;
+        match self {
+            CardLabel::Value => Vec3::new(-half_size, half_size, 0.1),
+            CardLabel::Suit => Vec3::new(half_size - layout.font_size(), half_size, 0.1),
+        }
+    }
+}
+
+/// A component representing a completed run drawn on the foundation.
+#[derive(Component)]
+struct Completed {
+    index: usize,
+}
+
 /// A component representing a card's position in the central grid.
-#[derive(Component, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Component, Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
 struct GridPosition {
     x: u8,
     y: u8,
@@ -48,135 +157,423 @@ impl GridPosition {
     }
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Debug, Clone)]
 struct Available {
-    cards: [Card; 10],
+    cards: Vec<Card>,
 }
 
 type LegalMoves = Vec<(GridPosition, GridPosition)>;
 
+/// A resource holding the suits of the runs that have been completed and removed from the grid.
+#[derive(Debug, Default, Clone)]
+struct Foundation {
+    runs: Vec<CardSuit>,
+}
+
 const WINDOW_WIDTH: f32 = 960.0;
 const WINDOW_HEIGHT: f32 = 540.0;
 const SPACING: f32 = 5.0;
-const SPACING_Y: f32 = 25.0;
-const CARD_SIZE: f32 = (WINDOW_WIDTH - SPACING * 11.0) / 10.0;
-const AVAILABLE_X: f32 = WINDOW_WIDTH / 2.0 - SPACING;
-const AVAILABLE_Y: f32 = WINDOW_HEIGHT / 2.0 - SPACING - CARD_SIZE / 2.0;
-const GRID_X: f32 = WINDOW_WIDTH / -2.0 + SPACING;
-const GRID_Y: f32 = WINDOW_HEIGHT / 2.0 - SPACING - CARD_SIZE - SPACING;
+
+/// A resource holding the seed of the game being played, and the seed to deal the next game with.
+#[derive(Debug, Default)]
+struct GameSeed {
+    /// The seed asked for on the command line, used for the next game only.
+    requested: Option<u64>,
+    /// The seed the current game was dealt with.
+    current: u64,
+}
 
 fn main() {
+    let args = match cli::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    let mut settings = load_settings();
+    let difficulty = args.difficulty.unwrap_or(settings.difficulty);
+    let variant = args.variant.unwrap_or(settings.variant);
+    if args.headless {
+        let seed = args.seed.unwrap_or_else(|| variant.rules().random_seed());
+        print_deal(variant, difficulty, seed);
+        return;
+    }
+    settings.window_width = args.width.unwrap_or(settings.window_width);
+    settings.window_height = args.height.unwrap_or(settings.window_height);
+    settings = settings.validated();
+    let initial_state = if args.replay.is_some() {
+        AppState::Replay
+    } else if args.skips_menu() {
+        AppState::Playing
+    } else {
+        AppState::Menu
+    };
+
     App::new()
         .insert_resource(WindowDescriptor {
             title: String::from("Spidertaire"),
-            width: WINDOW_WIDTH,
-            height: WINDOW_HEIGHT,
+            width: settings.window_width,
+            height: settings.window_height,
             ..default()
         })
         .insert_resource(Msaa { samples: 4 })
         .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin)
+        .add_plugin(AssistPlugin)
+        .add_plugin(GamepadPlugin)
+        .add_plugin(HintPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(LayoutPlugin)
+        .add_plugin(PilesPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(ScreensPlugin)
+        .add_plugin(SelectionPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(StatsPlugin)
+        .add_plugin(TouchPlugin)
+        .add_state(initial_state)
+        .insert_resource(difficulty)
+        .insert_resource(variant)
+        .insert_resource(settings)
+        .insert_resource(GameSeed {
+            requested: args.seed,
+            current: 0,
+        })
+        .insert_resource(ReplaySource(args.replay))
+        .insert_resource(Game::with_variant(variant, difficulty, 0))
         .init_resource::<LegalMoves>()
+        .init_resource::<HashMap<GridPosition, Entity>>()
+        .init_resource::<Vec<Available>>()
+        .init_resource::<Foundation>()
+        .add_event::<GameAction>()
         .add_startup_system(setup)
-        .add_system_to_stage(CoreStage::PreUpdate, calculate_legal_moves)
-        .add_system_to_stage(CoreStage::PreUpdate, handle_grid_input)
-        .add_system_to_stage(CoreStage::PreUpdate, handle_available_input)
-        .add_system_to_stage(CoreStage::PreUpdate, show_revealed_cards)
+        .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(teardown_game))
+        .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(new_game))
+        .add_system_set(
+            SystemSet::on_update(AppState::Playing)
+                .with_system(calculate_legal_moves.label(PlayingSystem::LegalMoves))
+                .with_system(
+                    check_game_over
+                        .label(PlayingSystem::GameOver)
+                        .after(PlayingSystem::LegalMoves),
+                )
+                .with_system(
+                    handle_grid_input
+                        .label(PlayingSystem::Input)
+                        .after(PlayingSystem::GameOver),
+                )
+                .with_system(
+                    handle_available_input
+                        .label(PlayingSystem::Input)
+                        .after(PlayingSystem::GameOver),
+                )
+                .with_system(
+                    apply_game_actions
+                        .label(PlayingSystem::Action)
+                        .after(PlayingSystem::Selection),
+                )
+                .with_system(
+                    sync_with_game
+                        .label(PlayingSystem::Collect)
+                        .after(PlayingSystem::Action),
+                )
+                .with_system(pause_game),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Replay)
+                .with_system(apply_game_actions.label(PlayingSystem::Action))
+                .with_system(
+                    sync_with_game
+                        .label(PlayingSystem::Collect)
+                        .after(PlayingSystem::Action),
+                ),
+        )
+        .add_system_set(SystemSet::on_update(AppState::Paused).with_system(resume_game))
         .add_system(draw_available_sets)
         .add_system(draw_grid_hidden)
         .add_system(draw_grid_shown)
+        .add_system(draw_completed_runs)
+        .add_system(position_grid_cards)
+        .add_system(resize_cards)
         .run();
 }
 
 fn setup(mut commands: Commands) {
     commands.spawn_bundle(Camera2dBundle::default());
-    new_game(commands, Difficulty::Easy);
 }
 
-fn new_game(mut commands: Commands, difficulty: Difficulty) {
-    let mut deck = match difficulty {
-        Difficulty::Easy => Deck::from_suit(CardSuit::Spades),
-        Difficulty::Medium => Deck::from_suits(CardSuit::Spades, CardSuit::Hearts),
-        Difficulty::Hard => Deck::new(),
-    };
-    deck.combine(deck.clone());
-    deck.shuffle();
-    let mut grid_cards = HashMap::<GridPosition, Entity>::with_capacity(54);
-    for (pos, card) in deck.cards.drain(0..44).enumerate() {
-        let position = GridPosition {
-            x: (pos % 10) as u8,
-            y: (pos / 10) as u8,
-        };
-        let entity = commands
-            .spawn()
-            .insert(CardGui { card })
-            .insert(Hidden)
-            .insert(position)
-            .id();
-        grid_cards.insert(position, entity);
-    }
-    for (pos, card) in deck.cards.drain(0..10).enumerate() {
-        let position = GridPosition {
-            x: ((pos + 44) % 10) as u8,
-            y: ((pos + 44) / 10) as u8,
-        };
-        let entity = commands
-            .spawn()
-            .insert(CardGui { card })
-            .insert(Shown)
-            .insert(position)
-            .id();
-        grid_cards.insert(position, entity);
+/// A query for the entities that make up a game in progress.
+type GameEntities<'w, 's> =
+    Query<'w, 's, Entity, Or<(With<CardGui>, With<Available>, With<Completed>)>>;
+
+/// Despawns every card and stock entity, and empties the resources describing the game.
+fn clear_game(
+    commands: &mut Commands,
+    game_entities: &GameEntities,
+    grid_cards: &mut HashMap<GridPosition, Entity>,
+    available_sets: &mut Vec<Available>,
+    legal_moves: &mut LegalMoves,
+    foundation: &mut Foundation,
+) {
+    for e in game_entities.iter() {
+        commands.entity(e).despawn_recursive();
     }
-    commands.insert_resource(grid_cards);
+    grid_cards.clear();
+    available_sets.clear();
+    legal_moves.clear();
+    foundation.runs.clear();
+}
+
+fn teardown_game(
+    mut commands: Commands,
+    game_entities: GameEntities,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut available_sets: ResMut<Vec<Available>>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut foundation: ResMut<Foundation>,
+) {
+    clear_game(
+        &mut commands,
+        &game_entities,
+        &mut grid_cards,
+        &mut available_sets,
+        &mut legal_moves,
+        &mut foundation,
+    );
+}
 
-    let mut available_sets = Vec::with_capacity(5);
-    for set in deck.cards.chunks_exact(10) {
+/// Replaces every card with those of the given game, without animating.
+fn respawn_game(
+    commands: &mut Commands,
+    game: &Game,
+    game_entities: &GameEntities,
+    grid_cards: &mut HashMap<GridPosition, Entity>,
+    available_sets: &mut Vec<Available>,
+    legal_moves: &mut LegalMoves,
+    foundation: &mut Foundation,
+) {
+    clear_game(
+        commands,
+        game_entities,
+        grid_cards,
+        available_sets,
+        legal_moves,
+        foundation,
+    );
+    spawn_game(commands, game, grid_cards, available_sets, foundation);
+}
+
+/// Prints the deal for the given variant, difficulty and seed, with face down cards in brackets.
+fn print_deal(variant: Variant, difficulty: Difficulty, seed: u64) {
+    println!("{} ({}), seed {}", variant.as_str(), difficulty, seed);
+    println!("{}", Game::with_variant(variant, difficulty, seed));
+}
+
+/// Spawns the cards of a game, after any previous game has been cleared.
+fn spawn_game(
+    commands: &mut Commands,
+    game: &Game,
+    grid_cards: &mut HashMap<GridPosition, Entity>,
+    available_sets: &mut Vec<Available>,
+    foundation: &mut Foundation,
+) {
+    for (x, column) in game.columns().iter().enumerate() {
+        for (y, tableau_card) in column.iter().enumerate() {
+            let position = GridPosition {
+                x: x as u8,
+                y: y as u8,
+            };
+            let entity =
+                spawn_grid_card(commands, tableau_card.card, position, tableau_card.face_up);
+            grid_cards.insert(position, entity);
+        }
+    }
+    for cards in game.stock() {
         let available = Available {
-            cards: set.to_owned().try_into().unwrap(),
+            cards: cards.clone(),
         };
         commands.spawn().insert(available.clone());
         available_sets.push(available);
     }
-    commands.insert_resource(available_sets);
+    for (index, suit) in game.foundation().iter().enumerate() {
+        spawn_completed_run(commands, *suit, index);
+    }
+    foundation.runs = game.foundation().to_vec();
 }
 
-fn calculate_legal_moves(
+fn new_game(
+    mut commands: Commands,
+    variant: Res<Variant>,
+    difficulty: Res<Difficulty>,
+    mut seed: ResMut<GameSeed>,
+    mut game: ResMut<Game>,
+    game_entities: GameEntities,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut available_sets: ResMut<Vec<Available>>,
     mut legal_moves: ResMut<LegalMoves>,
-    grid_cards: Res<HashMap<GridPosition, Entity>>,
-    query: Query<(&CardGui, &Shown, &GridPosition)>,
+    mut foundation: ResMut<Foundation>,
 ) {
-    let other_cards = query
-        .iter()
-        .collect::<Vec<(&CardGui, &Shown, &GridPosition)>>();
-    let mut new_legal_moves = Vec::with_capacity(40);
-    for card in other_cards.clone() {
-        for other_card in &other_cards {
-            if let Some(value) = other_card.0.card.value.next() {
-                if value == card.0.card.value {
-                    let new_position = other_card.2.next_row();
-                    if grid_cards.get(&new_position).is_none() {
-                        new_legal_moves.push((card.2.to_owned(), new_position));
-                    }
-                }
-            }
+    clear_game(
+        &mut commands,
+        &game_entities,
+        &mut grid_cards,
+        &mut available_sets,
+        &mut legal_moves,
+        &mut foundation,
+    );
+    seed.current = seed
+        .requested
+        .take()
+        .unwrap_or_else(|| variant.rules().random_seed());
+    bevy::log::info!(
+        "dealing {} {} game with seed {}",
+        *difficulty,
+        variant.as_str(),
+        seed.current
+    );
+    *game = Game::with_variant(*variant, *difficulty, seed.current);
+    spawn_game(
+        &mut commands,
+        &game,
+        &mut grid_cards,
+        &mut available_sets,
+        &mut foundation,
+    );
+}
+
+/// Spawns a card in the grid, either face up or face down.
+fn spawn_grid_card(
+    commands: &mut Commands,
+    card: Card,
+    position: GridPosition,
+    shown: bool,
+) -> Entity {
+    let mut entity = commands.spawn();
+    entity.insert(CardGui { card }).insert(position);
+    if shown {
+        entity.insert(Shown);
+    } else {
+        entity.insert(Hidden);
+    }
+    entity.id()
+}
+
+/// Spawns the king drawn on the foundation for the completed run at the given index.
+fn spawn_completed_run(commands: &mut Commands, suit: CardSuit, index: usize) {
+    commands
+        .spawn()
+        .insert(CardGui {
+            card: Card {
+                value: CardValue::K,
+                suit,
+            },
+        })
+        .insert(Completed { index });
+}
+
+/// Ends the game when every run has been completed, or when the stock is empty and no moves remain.
+fn check_game_over(mut state: ResMut<State<AppState>>, game: Res<Game>) {
+    // the status searches for moves once the stock is gone, so it is only checked after a change
+    if !game.is_changed() {
+        return;
+    }
+    if game.status() != Status::Playing {
+        if let Err(e) = state.set(AppState::GameOver) {
+            bevy::log::warn!("unable to end game: {:?}", e);
         }
     }
+}
+
+/// Determines whether escape or a gamepad's start button was just pressed, consuming the press.
+fn take_pause_pressed(
+    keyboard_input: &mut Input<KeyCode>,
+    gamepad_input: &mut Input<GamepadButton>,
+) -> bool {
+    let mut pressed = keyboard_input.clear_just_pressed(KeyCode::Escape);
+    let start_buttons = gamepad_input
+        .get_just_pressed()
+        .filter(|button| button.1 == GamepadButtonType::Start)
+        .copied()
+        .collect::<Vec<GamepadButton>>();
+    for button in start_buttons {
+        pressed |= gamepad_input.clear_just_pressed(button);
+    }
+    pressed
+}
+
+fn pause_game(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut gamepad_input: ResMut<Input<GamepadButton>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if take_pause_pressed(&mut keyboard_input, &mut gamepad_input) {
+        if let Err(e) = state.push(AppState::Paused) {
+            bevy::log::warn!("unable to pause game: {:?}", e);
+        }
+    }
+}
+
+fn resume_game(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut gamepad_input: ResMut<Input<GamepadButton>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if take_pause_pressed(&mut keyboard_input, &mut gamepad_input) {
+        if let Err(e) = state.pop() {
+            bevy::log::warn!("unable to resume game: {:?}", e);
+        }
+    }
+}
+
+/// Gets the grid positions of the first card of a move's run and of the card it lands on.
+fn grid_move(game: &Game, legal_move: Move) -> (GridPosition, GridPosition) {
+    let from = GridPosition {
+        x: legal_move.from as u8,
+        y: legal_move.row as u8,
+    };
+    let to = GridPosition {
+        x: legal_move.to as u8,
+        y: game.columns()[legal_move.to].len() as u8,
+    };
+    (from, to)
+}
+
+/// Lists the game's legal moves as grid positions, with moves onto cards before moves into empty columns.
+fn calculate_legal_moves(mut legal_moves: ResMut<LegalMoves>, game: Res<Game>) {
+    if !game.is_changed() {
+        return;
+    }
+    let mut new_legal_moves = game
+        .legal_moves()
+        .into_iter()
+        .map(|legal_move| grid_move(&game, legal_move))
+        .collect::<LegalMoves>();
+    new_legal_moves.sort_by_key(|(_, to)| to.y == 0);
     *legal_moves = new_legal_moves;
 }
 
 fn draw_available_sets(
     mut commands: Commands,
     query: Query<(Entity, &Available)>,
+    layout: Res<Layout>,
+    settings: Res<Settings>,
     assets: Res<AssetServer>,
 ) {
+    let scale = layout.texture_scale();
     for (pos, (e, _)) in query.iter().enumerate() {
-        let x = AVAILABLE_X - ((CARD_SIZE / 2.0) * pos as f32) - (CARD_SIZE / 2.0);
-        let y = AVAILABLE_Y;
-        let scale = CARD_SIZE / 200.0;
         commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: card_back_color(settings.card_back),
+                ..default()
+            },
             texture: assets.load("textures/back.png"),
             transform: Transform {
-                translation: Vec3::new(x, y, pos as f32),
+                translation: layout.available_center(pos).extend(pos as f32),
                 scale: Vec3::new(scale, scale, 1.0),
                 ..default()
             },
@@ -187,20 +584,22 @@ fn draw_available_sets(
 
 fn draw_grid_hidden(
     mut commands: Commands,
-    query: Query<(Entity, &Hidden, &GridPosition)>,
+    query: Query<(Entity, &GridPosition, Option<&Transform>), Added<Hidden>>,
+    layout: Res<Layout>,
+    settings: Res<Settings>,
     assets: Res<AssetServer>,
 ) {
-    for (e, _, pos) in query.iter() {
-        let x = GRID_X + ((CARD_SIZE + SPACING) * pos.x as f32) + (CARD_SIZE / 2.0);
-        let y = GRID_Y - (SPACING_Y * pos.y as f32) - (CARD_SIZE / 2.0);
-        let scale = CARD_SIZE / 200.0;
+    for (e, pos, transform) in query.iter() {
         commands.entity(e).insert_bundle(SpriteBundle {
-            texture: assets.load("textures/back.png"),
-            transform: Transform {
-                translation: Vec3::new(x, y, pos.y as f32),
-                scale: Vec3::new(scale, scale, 1.0),
+            sprite: Sprite {
+                color: card_back_color(settings.card_back),
+                custom_size: Some(Vec2::splat(layout.card_size)),
                 ..default()
             },
+            texture: assets.load("textures/back.png"),
+            transform: transform.copied().unwrap_or_else(|| {
+                Transform::from_translation(layout.grid_center(pos).extend(pos.y as f32))
+            }),
             ..default()
         });
     }
@@ -208,153 +607,266 @@ fn draw_grid_hidden(
 
 fn draw_grid_shown(
     mut commands: Commands,
-    query: Query<(Entity, &CardGui, &Shown, &GridPosition)>,
+    query: Query<(Entity, &CardGui, &GridPosition, Option<&Transform>), Added<Shown>>,
+    layout: Res<Layout>,
     assets: Res<AssetServer>,
 ) {
-    for (e, card, _, pos) in query.iter() {
-        let x = GRID_X + ((CARD_SIZE + SPACING) * pos.x as f32) + (CARD_SIZE / 2.0);
-        let y = GRID_Y - (SPACING_Y * pos.y as f32) - (CARD_SIZE / 2.0);
-        let color = match card.card.suit {
-            CardSuit::Hearts | CardSuit::Diamonds => Color::RED,
-            CardSuit::Clubs | CardSuit::Spades => Color::BLACK,
-        };
-        let font_size = 20.0;
-        commands
-            .entity(e)
-            .insert_bundle(SpriteBundle {
-                sprite: Sprite {
-                    color: Color::WHITE,
-                    custom_size: Some(Vec2::new(CARD_SIZE, CARD_SIZE)),
-                    ..Default::default()
-                },
-                transform: Transform {
-                    translation: Vec3::new(x, y, pos.y as f32),
-                    ..Default::default()
-                },
+    for (e, card, pos, transform) in query.iter() {
+        commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::splat(layout.card_size)),
                 ..Default::default()
-            })
-            .with_children(|parent| {
-                parent.spawn_bundle(Text2dBundle {
-                    text: Text::from_section(
-                        card.card.value.as_str(),
-                        TextStyle {
-                            font: assets.load("fonts/arial.ttf"),
-                            font_size,
-                            color,
-                        },
-                    ),
-                    transform: Transform::from_xyz(
-                        CARD_SIZE / -2.0,
-                        CARD_SIZE / 2.0,
-                        (pos.y + 1) as f32,
-                    ),
-                    ..Default::default()
-                });
-                parent.spawn_bundle(Text2dBundle {
+            },
+            transform: transform.copied().unwrap_or_else(|| {
+                Transform::from_translation(layout.grid_center(pos).extend(pos.y as f32))
+            }),
+            ..Default::default()
+        });
+        spawn_card_labels(&mut commands, e, &card.card, &layout, &assets);
+    }
+}
+
+fn draw_completed_runs(
+    mut commands: Commands,
+    query: Query<(Entity, &CardGui, &Completed, Option<&Children>)>,
+    game: Res<Game>,
+    layout: Res<Layout>,
+    assets: Res<AssetServer>,
+) {
+    for (e, card, completed, children) in query.iter() {
+        commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::splat(layout.card_size)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(completed_run_translation(
+                &game,
+                &layout,
+                card.card.suit,
+                completed.index,
+            )),
+            ..Default::default()
+        });
+        if children.is_none() {
+            spawn_card_labels(&mut commands, e, &card.card, &layout, &assets);
+        }
+    }
+}
+
+/// Gets the position of the king drawn for the completed run at the given index on the foundation.
+/// Runs overlap from the left, except in variants building the foundation by suit, where each one
+/// stays in its suit's foundation pile.
+fn completed_run_translation(game: &Game, layout: &Layout, suit: CardSuit, index: usize) -> Vec3 {
+    if game.rules().builds_foundation() {
+        layout.foundation_pile_center(suit.index()).extend(0.0)
+    } else {
+        layout.foundation_center(index).extend(index as f32)
+    }
+}
+
+/// Spawns the value and suit labels as children of a shown card.
+fn spawn_card_labels(
+    commands: &mut Commands,
+    e: Entity,
+    card: &Card,
+    layout: &Layout,
+    assets: &AssetServer,
+) {
+    let color = match card.color() {
+        CardColor::Red => Color::RED,
+        CardColor::Black => Color::BLACK,
+    };
+    commands.entity(e).with_children(|parent| {
+        for (label, value) in [
+            (CardLabel::Value, card.value.as_str()),
+            (CardLabel::Suit, card.suit.as_str()),
+        ] {
+            parent
+                .spawn_bundle(Text2dBundle {
                     text: Text::from_section(
-                        card.card.suit.as_str(),
+                        value,
                         TextStyle {
                             font: assets.load("fonts/arial.ttf"),
-                            font_size,
+                            font_size: layout.font_size(),
                             color,
                         },
                     ),
-                    transform: Transform::from_xyz(
-                        CARD_SIZE / 2.0 - 20.0,
-                        CARD_SIZE / 2.0,
-                        (pos.y + 1) as f32,
-                    ),
+                    transform: Transform::from_translation(label.translation(layout)),
                     ..Default::default()
-                });
-            });
-    }
+                })
+                .insert(label);
+        }
+    });
 }
 
-#[derive(Debug)]
-struct Bounds2 {
-    pub pos: Vec2,
-    pub size: Vec2,
+/// Moves grid cards towards the position given by the layout, animating the move when enabled.
+/// Cards being dragged are left where they are until they are dropped.
+fn position_grid_cards(
+    mut commands: Commands,
+    layout: Res<Layout>,
+    settings: Res<AnimationSettings>,
+    mut window_size: Local<Vec2>,
+    mut query: Query<
+        (
+            Entity,
+            &GridPosition,
+            &mut Transform,
+            Option<&mut MoveTween>,
+        ),
+        Without<Dragged>,
+    >,
+) {
+    let resized = *window_size != layout.window_size;
+    *window_size = layout.window_size;
+    for (e, pos, mut transform, tween) in query.iter_mut() {
+        let target = layout.grid_center(pos).extend(pos.y as f32);
+        if resized || !settings.enabled {
+            if transform.translation != target {
+                transform.translation = target;
+            }
+            if tween.is_some() {
+                commands.entity(e).remove::<MoveTween>();
+            }
+        } else if let Some(mut tween) = tween {
+            if tween.target() != target {
+                tween.retarget(transform.translation, target);
+            }
+        } else if transform.translation != target {
+            commands
+                .entity(e)
+                .insert(settings.move_tween(transform.translation, target));
+        }
+    }
 }
 
-impl Bounds2 {
-    pub fn contains(&self, coords: Vec2) -> bool {
-        coords.x >= self.pos.x
-            && coords.y >= self.pos.y
-            && coords.x <= self.pos.x + self.size.x
-            && coords.y <= self.pos.y + self.size.y
+/// Resizes cards and their labels when the layout changes.
+fn resize_cards(
+    layout: Res<Layout>,
+    mut cards: Query<&mut Sprite, With<CardGui>>,
+    mut labels: Query<(&CardLabel, &mut Transform, &mut Text)>,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+    let card_size = Some(Vec2::splat(layout.card_size));
+    for mut sprite in cards.iter_mut() {
+        if sprite.custom_size != card_size {
+            sprite.custom_size = card_size;
+        }
+    }
+    let font_size = layout.font_size();
+    for (label, mut transform, mut text) in labels.iter_mut() {
+        let translation = label.translation(&layout);
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+        if text.sections[0].style.font_size != font_size {
+            text.sections[0].style.font_size = font_size;
+        }
     }
 }
 
+/// Finds the move made when the run starting at the given position is tapped or clicked.
+fn find_auto_move(
+    legal_moves: &LegalMoves,
+    from: GridPosition,
+) -> Option<(GridPosition, GridPosition)> {
+    legal_moves
+        .iter()
+        .find(|legal_move| legal_move.0 == from)
+        .copied()
+}
+
+/// Finds the action taken when the card at the given position is tapped or clicked. The bottom card
+/// of a column is played to the foundation when it can be, and otherwise its run is moved, or
+/// failing that the bottom card is played into an empty free cell.
+fn find_auto_action(
+    game: &Game,
+    legal_moves: &LegalMoves,
+    from: GridPosition,
+) -> Option<GameAction> {
+    let column = from.x as usize;
+    let bottom = game
+        .columns()
+        .get(column)
+        .map_or(false, |cards| cards.len() == from.y as usize + 1);
+    let play = |to| Play {
+        from: Pile::Column(column),
+        to,
+    };
+    let to_foundation = Some(play(Pile::Foundation))
+        .filter(|play| bottom && game.check_play(*play).is_ok())
+        .map(GameAction::Play);
+    let to_free_cell = game
+        .free_cells()
+        .iter()
+        .position(Option::is_none)
+        .filter(|_| bottom)
+        .map(|cell| GameAction::Play(play(Pile::Cell(cell))));
+    to_foundation
+        .or_else(|| find_auto_move(legal_moves, from).map(|(from, to)| GameAction::Move(from, to)))
+        .or(to_free_cell)
+}
+
+/// Finds the play made when the card on top of the waste or in a free cell is tapped or clicked,
+/// preferring the foundation.
+fn find_pile_play(game: &Game, pile: Pile) -> Option<Play> {
+    // legal plays list the foundation first
+    game.legal_plays()
+        .into_iter()
+        .find(|play| play.from == pile)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_grid_input(
     mouse_input: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    last_touch: Res<LastTouch>,
     windows: Res<Windows>,
+    game: Res<Game>,
     legal_moves: Res<LegalMoves>,
-    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
-    mut query: Query<(&Shown, &mut GridPosition, &Transform)>,
+    layout: Res<Layout>,
+    mut actions: EventWriter<GameAction>,
+    query: Query<(&Shown, &GridPosition)>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Left) {
+    if mouse_input.just_pressed(MouseButton::Left) && !last_touch.suppresses_mouse(&time) {
         if let Some(press_location) = windows
             .get_primary()
             .and_then(|window| window.cursor_position())
         {
-            let window_size = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT);
-            let world_press_location = press_location - window_size / 2.0;
-            let mut successful_move = None;
-            for (_, mut position, transform) in query.iter_mut() {
-                let bounds = Bounds2 {
-                    pos: Vec2::new(
-                        transform.translation.x - CARD_SIZE / 2.0,
-                        transform.translation.y - CARD_SIZE / 2.0,
-                    ),
-                    size: Vec2::new(CARD_SIZE, CARD_SIZE),
-                };
-                if bounds.contains(world_press_location) {
-                    for legal_move in &(*legal_moves) {
-                        if legal_move.0 == *position {
-                            let entity = grid_cards
-                                .remove(&position)
-                                .expect("Grid cards and components are out of sync");
-                            bevy::log::error!("moving {:?} to {:?}", position, legal_move.1);
-                            grid_cards.insert(legal_move.1, entity);
-                            *position = legal_move.1;
-                            successful_move = Some(legal_move.to_owned());
-                            break;
-                        }
-                    }
-                    break;
-                }
-            }
-            if let Some(input_move) = successful_move {
-                for (_, mut position, _) in query.iter_mut() {
-                    if position.x == input_move.0.x && position.y > input_move.0.y {
-                        let y_diff = position.y - input_move.0.y;
-                        let entity = grid_cards
-                            .remove(&position)
-                            .expect("Grid cards and components are out of sync");
-                        let new_position = GridPosition {
-                            x: input_move.1.x,
-                            y: input_move.1.y + y_diff,
-                        };
-                        bevy::log::error!("moving {:?} to {:?}", position, new_position);
-                        grid_cards.insert(new_position, entity);
-                        *position = new_position;
-                    }
-                }
+            let world_press_location = layout.world_position(press_location);
+            let picked = pick(
+                query.iter().map(|(_, position)| Pickable {
+                    item: *position,
+                    bounds: layout.card_bounds(layout.grid_center(position)),
+                    depth: position.y as f32,
+                }),
+                world_press_location,
+            );
+            let action = picked.and_then(|picked| find_auto_action(&game, &legal_moves, picked));
+            if let Some(action) = action {
+                actions.send(action);
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_available_input(
-    mut commands: Commands,
     mouse_input: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    last_touch: Res<LastTouch>,
     windows: Res<Windows>,
-    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
-    mut available_sets: ResMut<Vec<Available>>,
-    query: Query<(Entity, &Available)>,
+    layout: Res<Layout>,
+    game: Res<Game>,
+    available_sets: Res<Vec<Available>>,
+    mut actions: EventWriter<GameAction>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Left) {
-        if available_sets.is_empty() {
+    if mouse_input.just_pressed(MouseButton::Left) && !last_touch.suppresses_mouse(&time) {
+        // clicking the empty stock turns the waste over
+        if available_sets.is_empty() && game.waste().is_empty() {
             bevy::log::trace!("not adding available set because available sets is empty");
             return;
         }
@@ -362,31 +874,10 @@ fn handle_available_input(
             .get_primary()
             .and_then(|window| window.cursor_position())
         {
-            let window_size = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT);
-            let world_press_location = press_location - window_size / 2.0;
-            let bounds = Bounds2 {
-                pos: Vec2::new(AVAILABLE_X - CARD_SIZE * 3.5, AVAILABLE_Y - CARD_SIZE / 2.0),
-                size: Vec2::new(CARD_SIZE, CARD_SIZE),
-            };
+            let world_press_location = layout.world_position(press_location);
+            let bounds = layout.available_bounds(available_sets.len().max(1));
             if bounds.contains(world_press_location) {
-                if let Some((e, _)) = query.iter().last() {
-                    commands.entity(e).despawn();
-                }
-                let max_rows = find_max_rows(grid_cards.keys());
-                bevy::log::error!("adding available set to max rows: {:?}", max_rows);
-                for (pos, card) in available_sets.remove(0).cards.into_iter().enumerate() {
-                    let position = GridPosition {
-                        x: pos as u8,
-                        y: max_rows[pos],
-                    };
-                    let entity = commands
-                        .spawn()
-                        .insert(CardGui { card })
-                        .insert(Shown)
-                        .insert(position)
-                        .id();
-                    grid_cards.insert(position, entity);
-                }
+                actions.send(GameAction::Deal);
             } else {
                 bevy::log::trace!(
                     "not adding available set because {:?} does not contain {:?}",
@@ -400,12 +891,138 @@ fn handle_available_input(
     }
 }
 
-fn find_max_rows<'a, T>(positions: T) -> [u8; 10]
+/// Performs [`GameAction`] events on the game, then moves or deals cards to match.
+/// Undoing respawns every card, as any number of cards may have changed, and so do plays and
+/// deals onto the waste, which leave the grid and stock out of step.
+#[allow(clippy::too_many_arguments)]
+fn apply_game_actions(
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    mut game: ResMut<Game>,
+    layout: Res<Layout>,
+    settings: Res<AnimationSettings>,
+    game_entities: GameEntities,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut available_sets: ResMut<Vec<Available>>,
+    mut legal_moves: ResMut<LegalMoves>,
+    mut foundation: ResMut<Foundation>,
+    stock: Query<Entity, With<Available>>,
+    mut positions: Query<&mut GridPosition>,
+) {
+    for action in actions.iter() {
+        let action = action.action();
+        if let Err(e) = game.perform(action) {
+            bevy::log::trace!("not performing {}: {}", action, e);
+            continue;
+        }
+        match action {
+            Action::Move(legal_move) => move_run(
+                &mut grid_cards,
+                &mut positions,
+                game.columns().len(),
+                legal_move,
+            ),
+            Action::Deal if !game.rules().has_waste() => {
+                if let Some(e) = stock.iter().nth(available_sets.len() - 1) {
+                    commands.entity(e).despawn();
+                }
+                let available = available_sets.remove(0);
+                deal_set(
+                    &mut commands,
+                    &layout,
+                    &settings,
+                    &mut grid_cards,
+                    game.columns().len(),
+                    available,
+                    available_sets.len(),
+                );
+            }
+            Action::Play(_) | Action::Deal | Action::Undo => respawn_game(
+                &mut commands,
+                &game,
+                &game_entities,
+                &mut grid_cards,
+                &mut available_sets,
+                &mut legal_moves,
+                &mut foundation,
+            ),
+        }
+    }
+}
+
+/// Moves the cards of a run to the positions given by a move the game has made.
+fn move_run(
+    grid_cards: &mut HashMap<GridPosition, Entity>,
+    positions: &mut Query<&mut GridPosition>,
+    columns: usize,
+    legal_move: Move,
+) {
+    let max_rows = find_max_rows(grid_cards.keys(), columns);
+    let (from, to) = (legal_move.from as u8, legal_move.to as u8);
+    for (offset, y) in (legal_move.row as u8..max_rows[from as usize]).enumerate() {
+        let position = GridPosition { x: from, y };
+        let new_position = GridPosition {
+            x: to,
+            y: max_rows[to as usize] + offset as u8,
+        };
+        let entity = grid_cards
+            .remove(&position)
+            .expect("Grid cards and the game are out of sync");
+        bevy::log::debug!("moving {:?} to {:?}", position, new_position);
+        if let Ok(mut grid_position) = positions.get_mut(entity) {
+            *grid_position = new_position;
+        }
+        grid_cards.insert(new_position, entity);
+    }
+}
+
+/// Spawns the cards of a set the game has dealt from the stock, one onto the end of each of the
+/// first columns. The index is the stock slot the set was drawn from, so the cards are animated from it.
+fn deal_set(
+    commands: &mut Commands,
+    layout: &Layout,
+    settings: &AnimationSettings,
+    grid_cards: &mut HashMap<GridPosition, Entity>,
+    columns: usize,
+    available: Available,
+    index: usize,
+) {
+    let max_rows = find_max_rows(grid_cards.keys(), columns);
+    let stock_center = layout.available_center(index);
+    for (pos, card) in available.cards.into_iter().enumerate() {
+        let position = GridPosition {
+            x: pos as u8,
+            y: max_rows[pos],
+        };
+        let mut entity = commands.spawn();
+        entity
+            .insert(CardGui { card })
+            .insert(Shown)
+            .insert(position);
+        if settings.enabled {
+            let to = layout.grid_center(&position).extend(position.y as f32);
+            let from = stock_center.extend(to.z);
+            entity
+                .insert_bundle(TransformBundle::from_transform(
+                    Transform::from_translation(from),
+                ))
+                .insert(settings.deal_tween(from, to, pos));
+        }
+        grid_cards.insert(position, entity.id());
+    }
+}
+
+/// Gets the number of rows in each of the given number of grid columns.
+fn find_max_rows<'a, T>(positions: T, columns: usize) -> Vec<u8>
 where
     T: Iterator<Item = &'a GridPosition>,
 {
-    let mut result = [0; 10];
+    let mut result = vec![0; columns];
     for pos in positions {
+        debug_assert!(
+            (pos.x as usize) < columns,
+            "Grid cards and the game are out of sync"
+        );
         if pos.y >= result[pos.x as usize] {
             result[pos.x as usize] = pos.y + 1;
         }
@@ -413,14 +1030,65 @@ where
     result
 }
 
-fn show_revealed_cards(
+/// Moves runs the game has completed from the grid to the foundation, and turns over cards the
+/// game has revealed.
+fn sync_with_game(
     mut commands: Commands,
-    grid_cards: Res<HashMap<GridPosition, Entity>>,
-    query: Query<(Entity, &GridPosition, &Hidden)>,
+    game: Res<Game>,
+    layout: Res<Layout>,
+    settings: Res<AnimationSettings>,
+    mut grid_cards: ResMut<HashMap<GridPosition, Entity>>,
+    mut foundation: ResMut<Foundation>,
+    hidden: Query<(), With<Hidden>>,
+    transforms: Query<&Transform>,
 ) {
-    for (e, position, _) in query.iter() {
-        if grid_cards.get(&position.next_row()).is_none() {
-            commands.entity(e).remove::<Hidden>().insert(Shown);
+    if !game.is_changed() {
+        return;
+    }
+    let max_rows = find_max_rows(grid_cards.keys(), game.columns().len());
+    for (x, column) in game.columns().iter().enumerate() {
+        // a completed run leaves the column shorter in the game than in the grid
+        if column.len() < max_rows[x] as usize {
+            let index = foundation.runs.len();
+            let target = match game.foundation().get(index) {
+                Some(suit) => completed_run_translation(&game, &layout, *suit, index),
+                None => layout.foundation_center(index).extend(index as f32),
+            };
+            for (order, y) in (column.len() as u8..max_rows[x]).rev().enumerate() {
+                let entity = grid_cards
+                    .remove(&GridPosition { x: x as u8, y })
+                    .expect("Grid cards and the game are out of sync");
+                match transforms.get(entity) {
+                    Ok(transform) if settings.enabled => {
+                        commands
+                            .entity(entity)
+                            .remove::<GridPosition>()
+                            .insert(settings.collect_tween(transform.translation, target, order));
+                    }
+                    _ => commands.entity(entity).despawn_recursive(),
+                }
+            }
+            if let Some(suit) = game.foundation().get(index) {
+                bevy::log::info!("collected completed run in column {}", x);
+                foundation.runs.push(*suit);
+                spawn_completed_run(&mut commands, *suit, index);
+            }
+        }
+        for (y, tableau_card) in column.iter().enumerate() {
+            let position = GridPosition {
+                x: x as u8,
+                y: y as u8,
+            };
+            match grid_cards.get(&position) {
+                Some(e) if tableau_card.face_up && hidden.get(*e).is_ok() => {
+                    let mut entity = commands.entity(*e);
+                    entity.remove::<Hidden>().insert(Shown);
+                    if settings.enabled {
+                        entity.insert(settings.flip());
+                    }
+                }
+                _ => {}
+            }
         }
     }
 }
@@ -0,0 +1,178 @@
+//! Recordings of games as their seed and every action taken, saved as TOML in the platform's data directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Action, Game};
+use crate::storage::data_dir;
+use crate::variant::Variant;
+use crate::Difficulty;
+
+/// The name of the directory replays are saved to inside the data directory.
+const REPLAY_DIR: &str = "replays";
+/// The extension of replay files.
+const REPLAY_EXTENSION: &str = "toml";
+
+/// A game that can be played back: the deal, and every move, deal and undo made after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    /// The variant played. Replays recorded before variants existed are of Spider.
+    #[serde(default)]
+    pub variant: Variant,
+    /// The difficulty the game was dealt at.
+    pub difficulty: Difficulty,
+    /// The seed the game was dealt with. It is saved as a string, as TOML integers cannot hold every seed.
+    #[serde(with = "seed_string")]
+    pub seed: u64,
+    /// Every move, deal and undo made, in order.
+    pub actions: Vec<Action>,
+}
+
+/// Saves seeds as strings.
+mod seed_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(seed)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<&Game> for Replay {
+    fn from(game: &Game) -> Self {
+        Replay {
+            variant: game.variant(),
+            difficulty: game.difficulty(),
+            seed: game.seed(),
+            actions: game.actions().to_vec(),
+        }
+    }
+}
+
+impl Replay {
+    /// Plays the first actions of the replay, giving the game as it was after them.
+    /// Returns an error if an action is not legal, which only happens if the replay was edited.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::replay::Replay;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let mut game = Game::new(Difficulty::Easy, 42);
+    /// game.perform(Action::Deal).unwrap();
+    /// game.perform(Action::Deal).unwrap();
+    /// let replay = Replay::from(&game);
+    /// assert_eq!(replay.game_at(1).unwrap().stock().len(), 4);
+    /// assert_eq!(replay.game_at(2).unwrap().columns(), game.columns());
+    /// ```
+    pub fn game_at(&self, step: usize) -> Result<Game, String> {
+        let mut game = Game::with_variant(self.variant, self.difficulty, self.seed);
+        for (index, action) in self.actions.iter().take(step).enumerate() {
+            game.perform(*action)
+                .map_err(|e| format!("action {} ({}) is not legal: {}", index + 1, action, e))?;
+        }
+        Ok(game)
+    }
+
+    /// Serializes the replay as TOML.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Replays are always representable as TOML")
+    }
+
+    /// Parses a replay from TOML.
+    ///
+    /// ```
+    /// use spidertaire::game::*;
+    /// use spidertaire::replay::Replay;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let replay = Replay {
+    ///     variant: Variant::Spider,
+    ///     difficulty: Difficulty::Hard,
+    ///     seed: u64::MAX,
+    ///     actions: vec![Action::Move(Move { from: 2, row: 3, to: 6 }), Action::Deal, Action::Undo],
+    /// };
+    /// let toml = replay.to_toml();
+    /// assert!(toml.contains(r#"actions = ["3:4->7", "deal", "undo"]"#));
+    /// assert_eq!(Replay::from_toml(&toml), Ok(replay));
+    /// assert!(Replay::from_toml("difficulty = \"Easy\"\nseed = \"1\"\nactions = [\"jump\"]").is_err());
+    ///
+    /// let old = Replay::from_toml("difficulty = \"Easy\"\nseed = \"1\"\nactions = []").unwrap();
+    /// assert_eq!(old.variant, Variant::Spider);
+    /// ```
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        toml::from_str(s).map_err(|e| e.to_string())
+    }
+
+    /// Gets the directory replays are saved to, if the platform has a data directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(REPLAY_DIR))
+    }
+
+    /// Gets the name of the file a game started at the given time since the Unix epoch is saved to.
+    /// Names sort in the order the games were started, and include the nanoseconds so games with
+    /// the same seed started within a second of each other are kept apart.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use spidertaire::replay::Replay;
+    ///
+    /// let first = Replay::file_name(Duration::new(1_700_000_000, 5), 42);
+    /// let second = Replay::file_name(Duration::new(1_700_000_000, 900_000_000), 42);
+    /// assert_eq!(first, "001700000000-000000005-42.toml");
+    /// assert!(first < second);
+    /// ```
+    pub fn file_name(started: Duration, seed: u64) -> String {
+        format!(
+            "{:012}-{:09}-{}.{}",
+            started.as_secs(),
+            started.subsec_nanos(),
+            seed,
+            REPLAY_EXTENSION
+        )
+    }
+
+    /// Finds the replay of the most recently started game in a directory, if there is one.
+    pub fn latest(dir: &Path) -> io::Result<Option<PathBuf>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut latest = None;
+        for entry in entries {
+            let path = entry?.path();
+            let is_replay = path
+                .extension()
+                .is_some_and(|extension| extension == REPLAY_EXTENSION);
+            if is_replay && latest.as_ref().is_none_or(|latest| path > *latest) {
+                latest = Some(path);
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Loads a replay from a file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let s = fs::read_to_string(path)?;
+        Self::from_toml(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Saves a replay to a file, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
@@ -0,0 +1,191 @@
+//! Player preferences, saved as TOML in the platform's config directory.
+
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::config_dir;
+use crate::variant::Variant;
+use crate::{CardSuit, Difficulty, Suits};
+
+/// The name of the file settings are saved to inside the config directory.
+const SETTINGS_FILE: &str = "settings.toml";
+/// The range of window widths accepted, in logical pixels.
+pub const WINDOW_WIDTH_RANGE: RangeInclusive<f32> = 320.0..=7680.0;
+/// The range of window heights accepted, in logical pixels.
+pub const WINDOW_HEIGHT_RANGE: RangeInclusive<f32> = 240.0..=4320.0;
+/// The range of animation speeds accepted, as a multiplier of the normal speed.
+pub const ANIMATION_SPEED_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+
+/// The design drawn on the back of face down cards.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardBack {
+    #[default]
+    Classic,
+    Red,
+    Blue,
+    Green,
+}
+
+impl CardBack {
+    /// Creates an array of all card backs.
+    pub fn all() -> [CardBack; 4] {
+        [
+            CardBack::Classic,
+            CardBack::Red,
+            CardBack::Blue,
+            CardBack::Green,
+        ]
+    }
+
+    /// Returns the display name of the card back.
+    pub fn as_str(&self) -> &str {
+        match self {
+            CardBack::Classic => "Classic",
+            CardBack::Red => "Red",
+            CardBack::Blue => "Blue",
+            CardBack::Green => "Green",
+        }
+    }
+}
+
+/// The player's preferences. Missing values take their defaults when loaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The width of the window, in logical pixels.
+    pub window_width: f32,
+    /// The height of the window, in logical pixels.
+    pub window_height: f32,
+    /// The variant selected when the game was last played.
+    pub variant: Variant,
+    /// The difficulty selected when the game was last played.
+    pub difficulty: Difficulty,
+    /// The suits and grouping of the custom difficulty offered on the menu.
+    pub custom_difficulty: Difficulty,
+    /// Whether cards are animated.
+    pub animations: bool,
+    /// A multiplier applied to the speed of every animation.
+    pub animation_speed: f32,
+    /// The design drawn on the back of face down cards.
+    pub card_back: CardBack,
+    /// Whether legal moves are highlighted.
+    pub assist: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_width: 960.0,
+            window_height: 540.0,
+            variant: Variant::default(),
+            difficulty: Difficulty::default(),
+            custom_difficulty: Difficulty::Custom {
+                suits: Suits::new([CardSuit::Spades, CardSuit::Hearts, CardSuit::Clubs]),
+                by_colour: false,
+            },
+            animations: true,
+            animation_speed: 1.0,
+            card_back: CardBack::default(),
+            assist: false,
+        }
+    }
+}
+
+/// Clamps a value to a range, replacing values that are not numbers with a default.
+fn clamp_or(value: f32, range: &RangeInclusive<f32>, default: f32) -> f32 {
+    if value.is_nan() {
+        default
+    } else {
+        value.clamp(*range.start(), *range.end())
+    }
+}
+
+impl Settings {
+    /// Brings any out of range values back within their accepted range.
+    ///
+    /// ```
+    /// use spidertaire::settings::*;
+    ///
+    /// let settings = Settings {
+    ///     window_width: 10.0,
+    ///     animation_speed: f32::NAN,
+    ///     ..Settings::default()
+    /// }
+    /// .validated();
+    /// assert_eq!(settings.window_width, 320.0);
+    /// assert_eq!(settings.animation_speed, 1.0);
+    /// ```
+    pub fn validated(self) -> Self {
+        let defaults = Settings::default();
+        Settings {
+            window_width: clamp_or(
+                self.window_width,
+                &WINDOW_WIDTH_RANGE,
+                defaults.window_width,
+            ),
+            window_height: clamp_or(
+                self.window_height,
+                &WINDOW_HEIGHT_RANGE,
+                defaults.window_height,
+            ),
+            animation_speed: clamp_or(
+                self.animation_speed,
+                &ANIMATION_SPEED_RANGE,
+                defaults.animation_speed,
+            ),
+            ..self
+        }
+    }
+
+    /// Serializes the settings as TOML.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Settings are always representable as TOML")
+    }
+
+    /// Parses settings from TOML, filling in missing values with defaults and bringing
+    /// out of range values back within range.
+    ///
+    /// ```
+    /// use spidertaire::settings::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let settings = Settings::from_toml("difficulty = \"Hard\"\nanimation_speed = 10.0").unwrap();
+    /// assert_eq!(settings.difficulty, Difficulty::Hard);
+    /// assert_eq!(settings.animation_speed, 4.0);
+    /// assert_eq!(settings.card_back, CardBack::Classic);
+    /// assert!(Settings::from_toml("difficulty = 3").is_err());
+    /// ```
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        toml::from_str::<Settings>(s)
+            .map(Settings::validated)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Gets the path settings are saved to, if the platform has a config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    /// Loads settings from a file, returning the default settings if it does not exist.
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => Self::from_toml(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves settings to a file, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
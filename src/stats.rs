@@ -0,0 +1,204 @@
+//! Statistics about the games a player has finished, kept separately for each variant and difficulty.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::data_dir;
+use crate::variant::Variant;
+use crate::Difficulty;
+
+/// The name of the file statistics are saved to inside the data directory.
+const STATS_FILE: &str = "stats.toml";
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every run was completed.
+    Won,
+    /// No moves remained.
+    Lost,
+    /// The game was left before it ended.
+    Abandoned,
+}
+
+/// The statistics for a single difficulty.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyStats {
+    /// The number of games finished or abandoned.
+    pub played: u32,
+    /// The number of games won.
+    pub won: u32,
+    /// The number of games won in a row, up to the most recent game.
+    pub current_streak: u32,
+    /// The most games ever won in a row.
+    pub best_streak: u32,
+    /// The highest score of any game that was won or lost.
+    pub best_score: Option<i32>,
+    /// The shortest time taken to win a game, in seconds.
+    pub fastest_win_seconds: Option<u64>,
+}
+
+impl DifficultyStats {
+    /// Gets the percentage of games played that were won.
+    ///
+    /// ```
+    /// use spidertaire::stats::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut stats = DifficultyStats::default();
+    /// assert_eq!(stats.win_percentage(), 0.0);
+    /// stats.record(Outcome::Won, 900, Duration::from_secs(300));
+    /// stats.record(Outcome::Lost, 450, Duration::from_secs(600));
+    /// assert_eq!(stats.win_percentage(), 50.0);
+    /// ```
+    pub fn win_percentage(&self) -> f32 {
+        if self.played == 0 {
+            0.0
+        } else {
+            self.won as f32 * 100.0 / self.played as f32
+        }
+    }
+
+    /// Records the end of a game. Abandoned games count as played and end the winning streak,
+    /// but their score is not recorded.
+    ///
+    /// ```
+    /// use spidertaire::stats::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut stats = DifficultyStats::default();
+    /// stats.record(Outcome::Won, 900, Duration::from_secs(300));
+    /// stats.record(Outcome::Won, 850, Duration::from_secs(240));
+    /// stats.record(Outcome::Abandoned, 400, Duration::from_secs(60));
+    /// assert_eq!(stats.played, 3);
+    /// assert_eq!(stats.won, 2);
+    /// assert_eq!(stats.current_streak, 0);
+    /// assert_eq!(stats.best_streak, 2);
+    /// assert_eq!(stats.best_score, Some(900));
+    /// assert_eq!(stats.fastest_win_seconds, Some(240));
+    /// ```
+    pub fn record(&mut self, outcome: Outcome, score: i32, elapsed: Duration) {
+        self.played += 1;
+        match outcome {
+            Outcome::Won => {
+                self.won += 1;
+                self.current_streak += 1;
+                self.best_streak = self.best_streak.max(self.current_streak);
+                let seconds = elapsed.as_secs();
+                self.fastest_win_seconds = Some(
+                    self.fastest_win_seconds
+                        .map_or(seconds, |fastest| fastest.min(seconds)),
+                );
+            }
+            Outcome::Lost | Outcome::Abandoned => self.current_streak = 0,
+        }
+        if outcome != Outcome::Abandoned {
+            self.best_score = Some(self.best_score.map_or(score, |best| best.max(score)));
+        }
+    }
+}
+
+/// Statistics for every difficulty that has been played, keyed by the difficulty's name.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// The statistics for each difficulty.
+    pub difficulties: BTreeMap<String, DifficultyStats>,
+}
+
+impl Statistics {
+    /// Gets the name the statistics for a variant and difficulty are kept under.
+    /// Spider uses the name of the difficulty alone, as it did before there were other variants,
+    /// and variants that ignore the difficulty use their name alone.
+    ///
+    /// ```
+    /// use spidertaire::stats::Statistics;
+    /// use spidertaire::variant::Variant;
+    /// use spidertaire::Difficulty;
+    ///
+    /// assert_eq!(Statistics::key(Variant::Spider, Difficulty::Hard), "Hard");
+    /// assert_eq!(Statistics::key(Variant::Spiderette, Difficulty::Easy), "Spiderette Easy");
+    ///
+    /// let suits = "shc".parse().unwrap();
+    /// let custom = Difficulty::Custom { suits, by_colour: true };
+    /// assert_eq!(Statistics::key(Variant::Spider, custom), "Custom HCS by colour");
+    /// assert_eq!(Statistics::key(Variant::Klondike, Difficulty::Easy), "Klondike");
+    /// assert_eq!(Statistics::key(Variant::Klondike, Difficulty::Hard), "Klondike");
+    /// assert_eq!(Statistics::key(Variant::FreeCell, Difficulty::Medium), "FreeCell");
+    /// ```
+    pub fn key(variant: Variant, difficulty: Difficulty) -> String {
+        match variant {
+            Variant::Spider => difficulty.to_string(),
+            _ if !variant.rules().uses_difficulty() => variant.as_str().to_owned(),
+            _ => format!("{} {}", variant.as_str(), difficulty),
+        }
+    }
+
+    /// Gets the statistics for a difficulty, which are empty if it has never been played.
+    pub fn get(&self, difficulty: &str) -> DifficultyStats {
+        self.difficulties
+            .get(difficulty)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Records the end of a game played at the given difficulty.
+    pub fn record(&mut self, difficulty: &str, outcome: Outcome, score: i32, elapsed: Duration) {
+        self.difficulties
+            .entry(difficulty.to_owned())
+            .or_default()
+            .record(outcome, score, elapsed);
+    }
+
+    /// Forgets every game played.
+    pub fn reset(&mut self) {
+        self.difficulties.clear();
+    }
+
+    /// Serializes the statistics as TOML.
+    ///
+    /// ```
+    /// use spidertaire::stats::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut stats = Statistics::default();
+    /// stats.record("Easy", Outcome::Won, 900, Duration::from_secs(300));
+    /// let parsed = Statistics::from_toml(&stats.to_toml()).unwrap();
+    /// assert_eq!(parsed, stats);
+    /// ```
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Statistics are always representable as TOML")
+    }
+
+    /// Parses statistics from TOML.
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        toml::from_str(s).map_err(|e| e.to_string())
+    }
+
+    /// Gets the path statistics are saved to, if the platform has a data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(STATS_FILE))
+    }
+
+    /// Loads statistics from a file, returning empty statistics if it does not exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => Self::from_toml(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves statistics to a file, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
@@ -0,0 +1,50 @@
+//! Locations of the files the game keeps between runs.
+
+use std::env;
+use std::path::PathBuf;
+
+/// The name of the directory created inside the platform's data and config directories.
+const APP_DIR: &str = "spidertaire";
+
+/// Gets the directory for files the game creates, such as statistics, following the platform's convention.
+/// Returns `None` when there is no such directory, for example on the web.
+pub fn data_dir() -> Option<PathBuf> {
+    platform_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join(APP_DIR))
+}
+
+/// Gets the directory for files the player may edit, such as settings, following the platform's convention.
+/// Returns `None` when there is no such directory, for example on the web.
+pub fn config_dir() -> Option<PathBuf> {
+    platform_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR))
+}
+
+/// Gets the platform's directory for per-user data of the given kind.
+/// On Linux the XDG variable is used if set, falling back to the given path inside the home directory.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_dir(xdg_var: &str, home_fallback: &str) -> Option<PathBuf> {
+    env::var_os(xdg_var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback)))
+}
+
+/// Gets the platform's directory for per-user data of the given kind.
+/// On macOS this is always the application support directory.
+#[cfg(target_os = "macos")]
+fn platform_dir(_xdg_var: &str, _home_fallback: &str) -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+}
+
+/// Gets the platform's directory for per-user data of the given kind.
+/// On Windows this is always the roaming application data directory.
+#[cfg(windows)]
+fn platform_dir(_xdg_var: &str, _home_fallback: &str) -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+/// Gets the platform's directory for per-user data of the given kind.
+/// Other platforms, such as the web, have no filesystem to write to.
+#[cfg(not(any(unix, windows)))]
+fn platform_dir(_xdg_var: &str, _home_fallback: &str) -> Option<PathBuf> {
+    None
+}
@@ -0,0 +1,610 @@
+//! The rule sets of the games in the Spider family.
+//!
+//! A [`Rules`] implementation describes everything that differs between variants: the cards
+//! played with, how they are dealt, how the stock is dealt, which moves are legal and when a run
+//! is complete. The [`Game`](crate::game::Game) engine and the front ends only ever ask the rules,
+//! so a variant is added by implementing [`Rules`] and listing it in [`Variant`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game::TableauCard;
+use crate::{Card, CardSuit, CardValue, Deck, Difficulty};
+
+/// The number of cards dealt to a column at the start of a game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnLayout {
+    /// The number of cards dealt face down.
+    pub hidden: usize,
+    /// The number of cards dealt face up on top of them.
+    pub shown: usize,
+}
+
+impl ColumnLayout {
+    /// Gets the total number of cards dealt to the column.
+    pub fn len(&self) -> usize {
+        self.hidden + self.shown
+    }
+
+    /// Determines whether no cards are dealt to the column.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The rules of a variant.
+///
+/// Only the layout and the number of decks must be given; every other rule defaults to the
+/// rules of Spider.
+pub trait Rules: fmt::Debug + Send + Sync {
+    /// The number of 52-card decks played with.
+    fn decks(&self) -> usize;
+
+    /// The cards dealt into each column at the start of a game, from the first column to the last.
+    /// Cards are dealt a row at a time, and the cards left over form the stock.
+    fn layout(&self) -> Vec<ColumnLayout>;
+
+    /// Creates the cards played with at the given difficulty, unshuffled.
+    fn deck(&self, difficulty: Difficulty) -> Deck {
+        difficulty.decks(self.decks())
+    }
+
+    /// Whether the difficulty changes the game. Variants that always deal every suit ignore it, so
+    /// their statistics and scores are the same at every difficulty.
+    fn uses_difficulty(&self) -> bool {
+        true
+    }
+
+    /// Creates the cards played with at the given difficulty, in the order they are dealt with the given seed.
+    fn shuffled_deck(&self, difficulty: Difficulty, seed: u64) -> Deck {
+        let mut deck = self.deck(difficulty);
+        deck.shuffle_seeded(seed);
+        deck
+    }
+
+    /// Picks the seed of a new game at random.
+    fn random_seed(&self) -> u64 {
+        rand::random()
+    }
+
+    /// The number of cards dealt from the stock at a time, one onto each of the first columns.
+    /// The last set is smaller when the stock does not divide evenly.
+    fn stock_set_size(&self) -> usize {
+        self.layout().len()
+    }
+
+    /// Whether every column must have a card before the stock can be dealt.
+    fn deal_needs_filled_columns(&self) -> bool {
+        true
+    }
+
+    /// Whether the stock is dealt face up onto a waste pile, whose top card can be played, rather
+    /// than onto the columns. Once the stock runs out, the waste is turned over to form it again.
+    fn has_waste(&self) -> bool {
+        false
+    }
+
+    /// Whether cards are played to the foundation one at a time, building up from the ace of each
+    /// suit, rather than leaving the tableau as completed runs.
+    fn builds_foundation(&self) -> bool {
+        false
+    }
+
+    /// The number of free cells, each of which can hold any single card. With free cells, runs are
+    /// moved a card at a time through them and the empty columns, limiting the length of a run
+    /// that can be moved at once.
+    fn free_cells(&self) -> usize {
+        0
+    }
+
+    /// Gets the number of cards at the bottom of a column that can be moved together at the given difficulty.
+    /// By default these are face up cards descending by one in a single suit, or in a single colour
+    /// when the difficulty groups runs by colour.
+    fn movable_run(&self, column: &[TableauCard], difficulty: Difficulty) -> usize {
+        let by_colour = difficulty.groups_by_colour();
+        let mut length = 0;
+        for (index, tableau_card) in column.iter().enumerate().rev() {
+            if !tableau_card.face_up {
+                break;
+            }
+            let continues = index + 1 == column.len() || {
+                let below = column[index + 1].card;
+                if by_colour {
+                    below.is_next_in_color(&tableau_card.card)
+                } else {
+                    below.is_next_in_suit(&tableau_card.card)
+                }
+            };
+            if !continues {
+                break;
+            }
+            length += 1;
+        }
+        length
+    }
+
+    /// Describes the runs [`Rules::movable_run`] allows to be moved at the given difficulty, for error messages.
+    fn movable_run_description(&self, difficulty: Difficulty) -> &str {
+        if difficulty.groups_by_colour() {
+            "a face up run in one colour"
+        } else {
+            "a face up run in one suit"
+        }
+    }
+
+    /// Determines whether a run starting with the given card can be placed on the card at the bottom
+    /// of a column, or in an empty column when there is none.
+    /// By default a run can be placed on any card one higher, or in an empty column.
+    fn can_place(&self, moved: Card, target: Option<Card>) -> bool {
+        target.is_none_or(|target| moved.is_next_in_run(&target))
+    }
+
+    /// Gets the suit of the completed run at the bottom of a column, if there is one.
+    /// By default a run is complete when it is face up, runs from king to ace and is in a single suit.
+    fn completed_run(&self, column: &[TableauCard]) -> Option<CardSuit> {
+        let run_values = CardValue::iter().rev();
+        let run = column.get(column.len().checked_sub(run_values.len())?..)?;
+        let suit = run[0].card.suit;
+        let completed = run.iter().zip(run_values).all(|(tableau_card, value)| {
+            tableau_card.face_up
+                && tableau_card.card.value == value
+                && tableau_card.card.suit == suit
+        });
+        completed.then_some(suit)
+    }
+
+    /// The number of completed runs needed to win, one for every suit of every deck.
+    fn runs_to_win(&self) -> usize {
+        self.decks() * CardSuit::iter().len()
+    }
+}
+
+/// Gets the number of face up cards at the bottom of a column descending by one in alternating colours.
+fn alternating_run(column: &[TableauCard]) -> usize {
+    let mut length = 0;
+    for (index, tableau_card) in column.iter().enumerate().rev() {
+        let continues = index + 1 == column.len() || {
+            column[index + 1]
+                .card
+                .is_next_alternating(&tableau_card.card)
+        };
+        if !tableau_card.face_up || !continues {
+            break;
+        }
+        length += 1;
+    }
+    length
+}
+
+/// Spider: two decks dealt into ten columns, with the stock dealt ten cards at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spider;
+
+impl Rules for Spider {
+    fn decks(&self) -> usize {
+        2
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        (0..10)
+            .map(|column| ColumnLayout {
+                hidden: if column < 4 { 5 } else { 4 },
+                shown: 1,
+            })
+            .collect()
+    }
+}
+
+/// Spiderette: a single deck dealt into seven columns like Klondike, with the stock dealt seven
+/// cards at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spiderette;
+
+impl Rules for Spiderette {
+    fn decks(&self) -> usize {
+        1
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        (0..7)
+            .map(|column| ColumnLayout {
+                hidden: column,
+                shown: 1,
+            })
+            .collect()
+    }
+}
+
+/// Will o' the Wisp: Spiderette with three cards in every column, only the top one face up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WillOTheWisp;
+
+impl Rules for WillOTheWisp {
+    fn decks(&self) -> usize {
+        1
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        vec![
+            ColumnLayout {
+                hidden: 2,
+                shown: 1
+            };
+            7
+        ]
+    }
+}
+
+/// Spiderwort: three decks dealt into thirteen columns, with the stock dealt thirteen cards at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spiderwort;
+
+impl Rules for Spiderwort {
+    fn decks(&self) -> usize {
+        3
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        vec![
+            ColumnLayout {
+                hidden: 4,
+                shown: 1
+            };
+            13
+        ]
+    }
+}
+
+/// Scorpion: a single deck dealt face up into seven columns, except for three face down cards in
+/// each of the first four. Any face up card can be moved along with every card on top of it, but
+/// only onto the next higher card of its suit, or a king into an empty column. The three cards left
+/// over are dealt onto the first three columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scorpion;
+
+impl Rules for Scorpion {
+    fn decks(&self) -> usize {
+        1
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        (0..7)
+            .map(|column| {
+                let hidden = if column < 4 { 3 } else { 0 };
+                ColumnLayout {
+                    hidden,
+                    shown: 7 - hidden,
+                }
+            })
+            .collect()
+    }
+
+    fn stock_set_size(&self) -> usize {
+        3
+    }
+
+    fn deal_needs_filled_columns(&self) -> bool {
+        false
+    }
+
+    fn movable_run(&self, column: &[TableauCard], _difficulty: Difficulty) -> usize {
+        column
+            .iter()
+            .rev()
+            .take_while(|tableau_card| tableau_card.face_up)
+            .count()
+    }
+
+    fn movable_run_description(&self, _difficulty: Difficulty) -> &str {
+        "face up"
+    }
+
+    fn can_place(&self, moved: Card, target: Option<Card>) -> bool {
+        match target {
+            Some(target) => moved.is_next_in_suit(&target),
+            None => moved.value == CardValue::K,
+        }
+    }
+}
+
+/// Klondike: a single deck dealt into seven columns like Spiderette, with the stock turned onto
+/// the waste a set at a time. Runs descend in alternating colours, only a king can go into an
+/// empty column, and cards are built up by suit on the foundation. A full deck is always dealt,
+/// whatever the difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Klondike {
+    /// The number of cards turned onto the waste at a time.
+    pub draw: usize,
+}
+
+impl Rules for Klondike {
+    fn decks(&self) -> usize {
+        1
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        Spiderette.layout()
+    }
+
+    fn deck(&self, _difficulty: Difficulty) -> Deck {
+        Deck::new()
+    }
+
+    fn uses_difficulty(&self) -> bool {
+        false
+    }
+
+    fn stock_set_size(&self) -> usize {
+        self.draw
+    }
+
+    fn has_waste(&self) -> bool {
+        true
+    }
+
+    fn builds_foundation(&self) -> bool {
+        true
+    }
+
+    fn movable_run(&self, column: &[TableauCard], _difficulty: Difficulty) -> usize {
+        alternating_run(column)
+    }
+
+    fn movable_run_description(&self, _difficulty: Difficulty) -> &str {
+        "a face up run in alternating colours"
+    }
+
+    fn can_place(&self, moved: Card, target: Option<Card>) -> bool {
+        match target {
+            Some(target) => moved.is_next_alternating(&target),
+            None => moved.value == CardValue::K,
+        }
+    }
+
+    fn completed_run(&self, _column: &[TableauCard]) -> Option<CardSuit> {
+        None
+    }
+}
+
+/// The number of deals Microsoft FreeCell numbers, from one.
+const MICROSOFT_DEALS: u64 = 32000;
+
+/// FreeCell: a single deck dealt face up into eight columns, with four free cells and no stock.
+/// Runs descend in alternating colours, any card can go into an empty column, and cards are built
+/// up by suit on the foundation. Deals numbered from 1 to 32000 are the deals of Microsoft FreeCell
+/// with the same number, and a full deck is always dealt, whatever the difficulty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FreeCell;
+
+impl Rules for FreeCell {
+    fn decks(&self) -> usize {
+        1
+    }
+
+    fn layout(&self) -> Vec<ColumnLayout> {
+        (0..8)
+            .map(|column| ColumnLayout {
+                hidden: 0,
+                shown: if column < 4 { 7 } else { 6 },
+            })
+            .collect()
+    }
+
+    fn deck(&self, _difficulty: Difficulty) -> Deck {
+        Deck::new()
+    }
+
+    fn uses_difficulty(&self) -> bool {
+        false
+    }
+
+    /// Deals the cards in the order Microsoft FreeCell deals the game numbered with the seed,
+    /// using the low 32 bits of larger seeds.
+    ///
+    /// ```
+    /// use spidertaire::variant::*;
+    /// use spidertaire::{CardSuit, CardValue, Difficulty};
+    ///
+    /// let deck = FreeCell.shuffled_deck(Difficulty::Easy, 1);
+    /// assert_eq!(deck.cards.len(), 52);
+    /// assert_eq!(deck.cards[0].value, CardValue::J);
+    /// assert_eq!(deck.cards[0].suit, CardSuit::Diamonds);
+    /// ```
+    fn shuffled_deck(&self, _difficulty: Difficulty, seed: u64) -> Deck {
+        let mut state = seed as u32;
+        let mut random = || {
+            state = state.wrapping_mul(214013).wrapping_add(2531011);
+            (state >> 16) & 0x7fff
+        };
+        // the cards are numbered by value from the ace, and by suit from clubs
+        let suits = [
+            CardSuit::Clubs,
+            CardSuit::Diamonds,
+            CardSuit::Hearts,
+            CardSuit::Spades,
+        ];
+        let mut cards = (0..52)
+            .map(|index| Card {
+                value: CardValue::try_from(index / 4 + 1).expect("Every card number has a value"),
+                suit: suits[index as usize % 4],
+            })
+            .collect::<Vec<Card>>();
+        let mut dealt = Vec::with_capacity(cards.len());
+        while !cards.is_empty() {
+            let index = random() as usize % cards.len();
+            dealt.push(cards.swap_remove(index));
+        }
+        Deck { cards: dealt }
+    }
+
+    fn random_seed(&self) -> u64 {
+        rand::thread_rng().gen_range(1..=MICROSOFT_DEALS)
+    }
+
+    fn builds_foundation(&self) -> bool {
+        true
+    }
+
+    fn free_cells(&self) -> usize {
+        4
+    }
+
+    fn movable_run(&self, column: &[TableauCard], _difficulty: Difficulty) -> usize {
+        alternating_run(column)
+    }
+
+    fn movable_run_description(&self, _difficulty: Difficulty) -> &str {
+        "a run in alternating colours"
+    }
+
+    fn can_place(&self, moved: Card, target: Option<Card>) -> bool {
+        target.is_none_or(|target| moved.is_next_alternating(&target))
+    }
+
+    fn completed_run(&self, _column: &[TableauCard]) -> Option<CardSuit> {
+        None
+    }
+}
+
+/// An enumeration of the variants that can be played.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Spider,
+    Spiderette,
+    Scorpion,
+    WillOTheWisp,
+    Spiderwort,
+    Klondike,
+    KlondikeDrawThree,
+    FreeCell,
+}
+
+impl Variant {
+    /// Creates an array of all variants.
+    pub fn all() -> [Variant; 8] {
+        [
+            Variant::Spider,
+            Variant::Spiderette,
+            Variant::Scorpion,
+            Variant::WillOTheWisp,
+            Variant::Spiderwort,
+            Variant::Klondike,
+            Variant::KlondikeDrawThree,
+            Variant::FreeCell,
+        ]
+    }
+
+    /// Returns the display name of the variant.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Variant::Spider => "Spider",
+            Variant::Spiderette => "Spiderette",
+            Variant::Scorpion => "Scorpion",
+            Variant::WillOTheWisp => "Will o' the Wisp",
+            Variant::Spiderwort => "Spiderwort",
+            Variant::Klondike => "Klondike",
+            Variant::KlondikeDrawThree => "Klondike Draw Three",
+            Variant::FreeCell => "FreeCell",
+        }
+    }
+
+    /// Returns the name of the variant as typed on the command line, in lower case without spaces.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Variant::Spider => "spider",
+            Variant::Spiderette => "spiderette",
+            Variant::Scorpion => "scorpion",
+            Variant::WillOTheWisp => "will-o-the-wisp",
+            Variant::Spiderwort => "spiderwort",
+            Variant::Klondike => "klondike",
+            Variant::KlondikeDrawThree => "klondike-draw-three",
+            Variant::FreeCell => "freecell",
+        }
+    }
+
+    /// Gets the rules of the variant.
+    ///
+    /// ```
+    /// use spidertaire::variant::*;
+    /// use spidertaire::Difficulty;
+    ///
+    /// let rules = Variant::Spider.rules();
+    /// assert_eq!(rules.layout().len(), 10);
+    /// assert_eq!(rules.layout().iter().map(ColumnLayout::len).sum::<usize>(), 54);
+    /// assert_eq!(rules.runs_to_win(), 8);
+    ///
+    /// let rules = Variant::Spiderette.rules();
+    /// assert_eq!(rules.layout().len(), 7);
+    /// assert_eq!(rules.layout().iter().map(ColumnLayout::len).sum::<usize>(), 28);
+    /// assert_eq!(rules.runs_to_win(), 4);
+    ///
+    /// let rules = Variant::Scorpion.rules();
+    /// assert_eq!(rules.layout().iter().map(ColumnLayout::len).sum::<usize>(), 49);
+    /// assert_eq!(rules.stock_set_size(), 3);
+    ///
+    /// let rules = Variant::WillOTheWisp.rules();
+    /// assert_eq!(rules.layout().iter().map(ColumnLayout::len).sum::<usize>(), 21);
+    ///
+    /// let rules = Variant::Spiderwort.rules();
+    /// assert_eq!(rules.layout().len(), 13);
+    /// assert_eq!(rules.runs_to_win(), 12);
+    ///
+    /// let rules = Variant::KlondikeDrawThree.rules();
+    /// assert_eq!(rules.deck(Difficulty::Easy).cards.len(), 52);
+    /// assert_eq!(rules.stock_set_size(), 3);
+    /// assert!(rules.has_waste() && rules.builds_foundation());
+    ///
+    /// let rules = Variant::FreeCell.rules();
+    /// assert_eq!(rules.layout().iter().map(ColumnLayout::len).sum::<usize>(), 52);
+    /// assert_eq!(rules.free_cells(), 4);
+    /// assert!((1..=32000).contains(&rules.random_seed()));
+    /// ```
+    pub fn rules(&self) -> &'static dyn Rules {
+        match self {
+            Variant::Spider => &Spider,
+            Variant::Spiderette => &Spiderette,
+            Variant::Scorpion => &Scorpion,
+            Variant::WillOTheWisp => &WillOTheWisp,
+            Variant::Spiderwort => &Spiderwort,
+            Variant::Klondike => &Klondike { draw: 1 },
+            Variant::KlondikeDrawThree => &Klondike { draw: 3 },
+            Variant::FreeCell => &FreeCell,
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    /// Parses a variant from its display name or its [id](Variant::id), ignoring case.
+    ///
+    /// ```
+    /// use spidertaire::variant::Variant;
+    ///
+    /// assert_eq!("spider".parse::<Variant>(), Ok(Variant::Spider));
+    /// assert_eq!("Spiderette".parse::<Variant>(), Ok(Variant::Spiderette));
+    /// assert_eq!("will-o-the-wisp".parse::<Variant>(), Ok(Variant::WillOTheWisp));
+    /// assert_eq!("Will o' the Wisp".parse::<Variant>(), Ok(Variant::WillOTheWisp));
+    /// assert!("patience".parse::<Variant>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variant::all()
+            .into_iter()
+            .find(|variant| {
+                variant.id().eq_ignore_ascii_case(s) || variant.as_str().eq_ignore_ascii_case(s)
+            })
+            .ok_or_else(|| {
+                let names = Variant::all().map(|variant| variant.id());
+                format!(
+                    "{} is not a variant, expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}